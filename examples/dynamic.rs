@@ -5,9 +5,10 @@
 //!   which we can then update using queries as usual.
 //! - To update the text color, we use the `[c]` tag with named colors.
 //!   We simply update the color for the given name and it updates everywhere.
+//! - To fade between colors, we insert the new color with a transition.
 
 use bevy::prelude::*;
-use bevy_mod_bbcode::{Bbcode, BbcodePlugin, BbcodeSettings, ColorMap};
+use bevy_mod_bbcode::{Bbcode, BbcodePlugin, BbcodeSettings, ColorMap, ColorTransition};
 
 #[derive(Component, Clone)]
 struct TimeMarker;
@@ -16,7 +17,7 @@ fn main() {
     App::new()
         .add_plugins((DefaultPlugins, BbcodePlugin::new().with_fonts("fonts")))
         .add_systems(Startup, setup)
-        .add_systems(Update, (update_text, update_color))
+        .add_systems(Update, (update_text, update_color, flash_color))
         .run();
 }
 
@@ -24,7 +25,9 @@ fn setup(mut commands: Commands) {
     commands.spawn(Camera2d);
 
     commands.spawn((
-        Bbcode::new("Time passed: [m=time]0.0[/m] s with [c=rainbow]rainbow[/c]"),
+        Bbcode::new(
+            "Time passed: [m=time]0.0[/m] s with [c=rainbow]rainbow[/c] and [c=flash]flashing[/c]",
+        ),
         BbcodeSettings::new("Fira Sans", 40., Color::WHITE)
             // Register the marker component for the `m=time` tag
             .with_marker("time", TimeMarker),
@@ -43,3 +46,23 @@ fn update_color(time: Res<Time>, mut color_map: ResMut<ColorMap>) {
     // Updating a value in the color map will update that color wherever the same name is used!
     color_map.insert("rainbow", Hsva::hsv(hue, 1., 1.));
 }
+
+fn flash_color(time: Res<Time>, mut color_map: ResMut<ColorMap>, mut is_on: Local<bool>) {
+    let should_be_on = time.elapsed_secs() % 2. < 1.;
+    if *is_on == should_be_on {
+        return;
+    }
+    *is_on = should_be_on;
+
+    let color = if should_be_on {
+        Color::srgb(1., 0.3, 0.3)
+    } else {
+        Color::WHITE
+    };
+    // The text fades to the new color, without updating it every frame
+    color_map.insert_with_transition(
+        "flash",
+        color,
+        ColorTransition::new(0.5).with_easing(EaseFunction::CubicOut),
+    );
+}
@@ -0,0 +1,34 @@
+//! This example demonstrates the animated text effect tags.
+//!
+//! The parameters of the effects can be set via key/value parameters on the tags.
+//! With `BbcodeSettings::with_split_glyphs`, each character of text with effects is animated individually.
+
+use bevy::prelude::*;
+use bevy_mod_bbcode::{Bbcode, BbcodePlugin, BbcodeSettings};
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, BbcodePlugin::new().with_fonts("fonts")))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    commands.spawn((
+        Bbcode::new(
+            "[wave amp=6 freq=1.5]waving[/wave], [shake]shaking[/shake], [rainbow]rainbow[/rainbow] and [pulse freq=2]pulsing[/pulse] text",
+        ),
+        BbcodeSettings::new("Fira Sans", 40., Color::WHITE),
+    ));
+
+    commands.spawn((
+        Bbcode::new("[rainbow][wave]Every character on its own![/wave][/rainbow]"),
+        BbcodeSettings::new("Fira Sans", 40., Color::WHITE).with_split_glyphs(true),
+        Node {
+            top: Val::Px(60.),
+            ..default()
+        },
+    ));
+}
@@ -0,0 +1,41 @@
+//! This example demonstrates how text can be revealed character by character, like in a dialogue box.
+//!
+//! - The `BbcodeReveal` component reveals the text without parsing it again.
+//! - The `[pause]` and `[speed]` tags control the pacing inline.
+//! - Press space to skip to the end.
+
+use bevy::prelude::*;
+use bevy_mod_bbcode::{Bbcode, BbcodePlugin, BbcodeReveal, BbcodeRevealFinished, BbcodeSettings};
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, BbcodePlugin::new().with_fonts("fonts")))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (skip_reveal, log_finished))
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    commands.spawn((
+        Bbcode::new("Well...[pause=0.8] I guess [b]you[/b] are the hero? [speed=3]Let's go, quick, quick![/speed]"),
+        BbcodeSettings::new("Fira Sans", 40., Color::WHITE),
+        // Reveal 15 characters per second and wait a bit longer after punctuation
+        BbcodeReveal::new(15.).with_punctuation_pause(0.2),
+    ));
+}
+
+fn skip_reveal(keys: Res<ButtonInput<KeyCode>>, mut query: Query<&mut BbcodeReveal>) {
+    if keys.just_pressed(KeyCode::Space) {
+        for mut reveal in query.iter_mut() {
+            reveal.skip();
+        }
+    }
+}
+
+fn log_finished(mut events: EventReader<BbcodeRevealFinished>) {
+    for event in events.read() {
+        info!("Finished revealing the text of {}", event.entity);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::HashMap, fmt::Display, sync::Arc};
+use std::{borrow::Cow, fmt::Display, sync::Arc};
 
 pub mod parser;
 
@@ -8,13 +8,37 @@ pub enum BbcodeNode<'a> {
     Text(Cow<'a, str>),
 }
 
+/// The number of brackets from which text is wrapped in a `[noparse]` tag instead of escaping each bracket.
+const NOPARSE_BRACKET_THRESHOLD: usize = 4;
+
 impl Display for BbcodeNode<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             BbcodeNode::Tag(node) => node.fmt(f),
-            BbcodeNode::Text(text) => text.fmt(f),
+            BbcodeNode::Text(text) => fmt_text(text, f),
+        }
+    }
+}
+
+/// Write the text so that it's parsed back as the same text.
+fn fmt_text(text: &str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let bracket_count = text.chars().filter(|c| matches!(c, '[' | ']')).count();
+
+    // Text with many brackets, e.g. BBCode examples, is more readable without escapes
+    if bracket_count >= NOPARSE_BRACKET_THRESHOLD
+        && !text.to_ascii_lowercase().contains("[/noparse]")
+    {
+        return write!(f, "[noparse]{text}[/noparse]");
+    }
+
+    for c in text.chars() {
+        if matches!(c, '[' | ']' | '\\') {
+            write!(f, "\\")?;
         }
+        write!(f, "{c}")?;
     }
+
+    Ok(())
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -25,8 +49,8 @@ pub struct BbcodeTag<'a> {
     /// A simple parameter for the tag, e.g. `value` for `[tag=value]something[/tag]`.
     simple_param: Option<Cow<'a, str>>,
 
-    /// Complex parameters, e.g. the map `value1` -> `xxx`, `value2` -> `yyy` for `[tag value1=”xxx” value2=”yyy”]something[/tag]`.
-    complex_params: HashMap<&'a str, Cow<'a, str>>,
+    /// Complex parameters in the order they are written, e.g. `value1` -> `xxx`, `value2` -> `yyy` for `[tag value1=”xxx” value2=”yyy”]something[/tag]`.
+    complex_params: Vec<(&'a str, Cow<'a, str>)>,
 
     /// The child nodes (or text) contained inside this node.
     children: Vec<Arc<BbcodeNode<'a>>>,
@@ -38,7 +62,7 @@ impl<'a> BbcodeTag<'a> {
         Self {
             name,
             simple_param: None,
-            complex_params: HashMap::new(),
+            complex_params: Vec::new(),
             children: Vec::new(),
         }
     }
@@ -56,10 +80,26 @@ impl<'a> BbcodeTag<'a> {
         self
     }
 
+    /// Add a key/value parameter.
+    pub fn add_param<K: Into<&'a str>, V: Into<Cow<'a, str>>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> &mut Self {
+        let (key, value) = (key.into(), value.into());
+
+        // Later parameters with the same key replace the earlier ones
+        match self.complex_params.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.complex_params.push((key, value)),
+        }
+        self
+    }
+
     /// Add a key/value parameter.
     #[cfg(test)]
     pub fn with_param<K: Into<&'a str>, V: Into<Cow<'a, str>>>(mut self, key: K, value: V) -> Self {
-        self.complex_params.insert(key.into(), value.into());
+        self.add_param(key, value);
         self
     }
 
@@ -92,13 +132,32 @@ impl<'a> BbcodeTag<'a> {
     pub fn simple_param(&self) -> &Option<Cow<'a, str>> {
         &self.simple_param
     }
+
+    /// The complex (key/value) parameters of this tag, in the order they are written.
+    pub fn complex_params(&self) -> &[(&'a str, Cow<'a, str>)] {
+        &self.complex_params
+    }
+
+    /// If it exists, the value of the complex parameter with the given key.
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.complex_params
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.as_ref())
+    }
 }
 
 impl Display for BbcodeTag<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// Write the parameter so that it's parsed back as the same value.
         fn fmt_param(param: &str) -> String {
-            if param.contains(' ') {
-                format!("\"{param}\"")
+            let needs_quotes = param.is_empty()
+                || param
+                    .contains(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '"' | '\\'));
+
+            if needs_quotes {
+                let escaped = param.replace('\\', "\\\\").replace('"', "\\\"");
+                format!("\"{escaped}\"")
             } else {
                 param.to_owned()
             }
@@ -116,8 +175,21 @@ impl Display for BbcodeTag<'_> {
 
         write!(f, "]")?;
 
+        if parser::is_void_tag(self.name) {
+            return Ok(());
+        }
+
         for child in &self.children {
-            child.fmt(f)?;
+            match &**child {
+                // The content of raw tags is not escaped
+                BbcodeNode::Text(text) if parser::is_raw_tag(self.name) => text.fmt(f)?,
+                child => child.fmt(f)?,
+            }
+        }
+
+        // List items are closed implicitly
+        if self.name == parser::LIST_ITEM_TAG {
+            return Ok(());
         }
 
         write!(f, "[/{}]", self.name)?;
@@ -144,6 +216,41 @@ mod tests {
         assert_eq!(format!("{node}"), "[color=#FF00FF]test[/color]");
     }
 
+    #[test]
+    fn display_void_tag() {
+        let node = BbcodeTag::new("pause").with_simple_param("0.5");
+        assert_eq!(format!("{node}"), "[pause=0.5]");
+    }
+
+    #[test]
+    fn display_escaped_text() {
+        let node = BbcodeTag::new("b").with_text("[x] \\o/");
+        assert_eq!(format!("{node}"), r"[b]\[x\] \\o/[/b]");
+    }
+
+    #[test]
+    fn display_noparse_text() {
+        let node = BbcodeTag::new("i").with_text("use [b]bold[/b]");
+        assert_eq!(
+            format!("{node}"),
+            "[i][noparse]use [b]bold[/b][/noparse][/i]"
+        );
+    }
+
+    #[test]
+    fn display_raw_tag() {
+        let node = BbcodeTag::new("code").with_text("[b]x[/b]");
+        assert_eq!(format!("{node}"), "[code][b]x[/b][/code]");
+    }
+
+    #[test]
+    fn display_list() {
+        let node = BbcodeTag::new("list")
+            .with_tag(BbcodeTag::new("*").with_text("one"))
+            .with_tag(BbcodeTag::new("*").with_text("two"));
+        assert_eq!(format!("{node}"), "[list][*]one[*]two[/list]");
+    }
+
     #[test]
     fn display_image() {
         let node = BbcodeTag::new("img")
@@ -151,4 +258,24 @@ mod tests {
             .with_text("test");
         assert_eq!(format!("{node}"), r#"[img alt="example image"]test[/img]"#);
     }
+
+    #[test]
+    fn display_params_round_trip() {
+        let node = BbcodeTag::new("tooltip")
+            .with_simple_param("a\tb")
+            .with_param("x", "[c=red]y[/c]")
+            .with_param("q", r#"say "hi" \o/"#)
+            .with_param("empty", "")
+            .with_text("test");
+        let formatted = format!("{node}");
+
+        assert_eq!(
+            formatted,
+            "[tooltip=\"a\tb\" x=\"[c=red]y[/c]\" q=\"say \\\"hi\\\" \\\\o/\" empty=\"\"]test[/tooltip]"
+        );
+        assert_eq!(
+            parser::parse_bbcode(&formatted),
+            Ok(("", vec![Arc::new(BbcodeNode::Tag(node))]))
+        );
+    }
 }
@@ -2,24 +2,33 @@ use std::{borrow::Cow, sync::Arc};
 
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, tag, tag_no_case},
-    character::complete::{alpha1, char},
-    combinator::{map, opt, value, verify},
-    error::ParseError,
-    multi::{fold_many1, many0},
-    sequence::{delimited, preceded},
+    bytes::complete::{is_not, tag, tag_no_case, take_while1},
+    character::complete::{alphanumeric1, anychar, char, multispace0, multispace1},
+    combinator::{map, not, opt, peek, recognize, value, verify},
+    error::{ErrorKind, ParseError},
+    multi::{fold_many1, many0, many_till},
+    sequence::{delimited, preceded, separated_pair, terminated},
     IResult, Parser,
 };
 
 use super::{BbcodeNode, BbcodeTag};
 
+/// Tags which don't have any content and therefore no closing tag, e.g. `[pause=0.5]`.
+const VOID_TAGS: &[&str] = &["pause", "t", "hr"];
+
+/// Tags whose content is not parsed as BBCode, but kept as raw text, e.g. `[code][b]not bold[/b][/code]`.
+const RAW_TAGS: &[&str] = &["code", "tt", "noparse"];
+
+/// The tag for list items, which is implicitly closed by the next item or the end of the list.
+pub(crate) const LIST_ITEM_TAG: &str = "*";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum StringFragment<'a> {
     Literal(&'a str),
     EscapedChar(char),
 }
 
-pub fn parse_bbcode(input: &str) -> IResult<&str, Vec<Arc<BbcodeNode>>> {
+pub fn parse_bbcode(input: &str) -> IResult<&str, Vec<Arc<BbcodeNode<'_>>>> {
     parse_bbcode_internal(input)
 }
 
@@ -38,6 +47,29 @@ fn parse_node<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, Bb
 
 fn parse_tag<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, BbcodeTag<'a>, E> {
     let (input, mut tag) = parse_opening_tag(input)?;
+
+    if is_void_tag(tag.name) {
+        return Ok((input, tag));
+    }
+
+    if is_raw_tag(tag.name) {
+        let (input, content) = parse_raw_content(input, tag.name)?;
+        let (input, _) = parse_closing_tag(input, tag.name)?;
+
+        if !content.is_empty() {
+            tag.children
+                .push(Arc::new(BbcodeNode::Text(content.into())));
+        }
+
+        return Ok((input, tag));
+    }
+
+    if tag.name == LIST_ITEM_TAG {
+        let (input, children) = parse_list_item_content(input)?;
+        tag.children = children;
+        return Ok((input, tag));
+    }
+
     let (input, children) = parse_bbcode_internal(input)?;
     let (input, _) = parse_closing_tag(input, tag.name)?;
 
@@ -46,17 +78,67 @@ fn parse_tag<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, Bbc
     Ok((input, tag))
 }
 
+/// Determine if the tag with the given name doesn't have content and a closing tag.
+pub(crate) fn is_void_tag(tag_name: &str) -> bool {
+    VOID_TAGS
+        .iter()
+        .any(|void_tag| void_tag.eq_ignore_ascii_case(tag_name))
+}
+
+/// Determine if the content of the tag with the given name is kept as raw text.
+pub(crate) fn is_raw_tag(tag_name: &str) -> bool {
+    RAW_TAGS
+        .iter()
+        .any(|raw_tag| raw_tag.eq_ignore_ascii_case(tag_name))
+}
+
+fn parse_raw_content<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+    tag_name: &str,
+) -> IResult<&'a str, &'a str, E> {
+    // Everything up to the closing tag, including other tags and escape characters
+    recognize(many_till(
+        anychar,
+        peek(|input| parse_closing_tag(input, tag_name)),
+    ))(input)
+}
+
+fn parse_list_item_content<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, Vec<Arc<BbcodeNode<'a>>>, E> {
+    // The item ends at the next item or the end of the list
+    many0(map(
+        preceded(
+            not(peek(alt((tag("[*]"), tag_no_case("[/list]"))))),
+            parse_node,
+        ),
+        |element| element.into(),
+    ))(input)
+}
+
 fn parse_opening_tag<'a, E: ParseError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, BbcodeTag<'a>, E> {
-    let (mut input, mut tag) = map(preceded(char('['), alpha1), BbcodeTag::new)(input)?;
+    let (mut input, mut tag) = map(
+        preceded(char('['), alt((alphanumeric1, tag(LIST_ITEM_TAG)))),
+        BbcodeTag::new,
+    )(input)?;
 
-    if let Ok((new_input, simple_param)) = preceded(char('='), parse_param::<E>)(input) {
+    if let Ok((new_input, simple_param)) = preceded(char('='), parse_simple_param::<E>)(input) {
         tag.add_simple_param(simple_param);
         input = new_input;
     }
 
-    let (input, _) = char(']')(input)?;
+    let (input, complex_params) = many0(preceded(
+        multispace1,
+        separated_pair(parse_param_key, char('='), parse_param),
+    ))(input)?;
+
+    for (key, value) in complex_params {
+        tag.add_param(key, value);
+    }
+
+    let (input, _) = preceded(multispace0, char(']'))(input)?;
 
     Ok((input, tag))
 }
@@ -78,11 +160,68 @@ fn parse_text<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, Co
 fn parse_param<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, Cow<'a, str>, E> {
     alt((
         parse_quoted_string,
-        map(parse_literal("\"\\[]"), Cow::Borrowed),
+        map(parse_literal("\"\\[] \t\r\n"), Cow::Borrowed),
+    ))
+    .parse(input)
+}
+
+/// Parse the simple parameter of a tag, e.g. `Fira Sans` in `[font=Fira Sans]`.
+///
+/// Unquoted values may contain whitespace, but end before trailing whitespace
+/// and before the first key/value parameter, e.g. `[c=dark red alpha=0.5]`.
+fn parse_simple_param<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, Cow<'a, str>, E> {
+    alt((
+        parse_quoted_string,
+        map(parse_simple_literal, Cow::Borrowed),
     ))
     .parse(input)
 }
 
+fn parse_simple_literal<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    let (_, literal) = parse_literal("\"\\[]").parse(input)?;
+
+    let mut end = literal.len();
+    let mut after_whitespace = false;
+
+    for (index, c) in literal.char_indices() {
+        if !c.is_whitespace() {
+            after_whitespace = false;
+            continue;
+        }
+
+        if after_whitespace {
+            continue;
+        }
+
+        after_whitespace = true;
+
+        let rest = literal[index..].trim_start();
+        let is_key_value = terminated(parse_param_key::<E>, char::<_, E>('='))(rest).is_ok();
+
+        if rest.is_empty() || is_key_value {
+            end = index;
+            break;
+        }
+    }
+
+    if end == 0 {
+        return Err(nom::Err::Error(E::from_error_kind(
+            input,
+            ErrorKind::Verify,
+        )));
+    }
+
+    Ok((&input[end..], &literal[..end]))
+}
+
+fn parse_param_key<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '-')(input)
+}
+
 fn parse_quoted_string<'a, E: ParseError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, Cow<'a, str>, E> {
@@ -210,6 +349,151 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_complex_params() {
+        let input = r#"[wave amp=4 freq="2.5"]test[/wave]"#;
+        let expected_tag = BbcodeTag::new("wave")
+            .with_param("amp", "4")
+            .with_param("freq", "2.5")
+            .with_text("test");
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
+    #[test]
+    fn test_parse_simple_and_complex_params() {
+        let input = "[c=primary alpha=0.5 ]test[/c]";
+        let expected_tag = BbcodeTag::new("c")
+            .with_simple_param("primary")
+            .with_param("alpha", "0.5")
+            .with_text("test");
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
+    #[test]
+    fn test_parse_simple_param_with_whitespace() {
+        let input = "[font=Fira Sans]test[/font]";
+        let expected_tag = BbcodeTag::new("font")
+            .with_simple_param("Fira Sans")
+            .with_text("test");
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
+    #[test]
+    fn test_parse_simple_param_with_whitespace_and_complex_params() {
+        let input = "[c=dark red alpha=0.5 ]test[/c]";
+        let expected_tag = BbcodeTag::new("c")
+            .with_simple_param("dark red")
+            .with_param("alpha", "0.5")
+            .with_text("test");
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
+    #[test]
+    fn test_parse_void_tag() {
+        let input = "wait[pause=0.5] for it";
+        let expected_tag = BbcodeTag::new("pause").with_simple_param("0.5");
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok((
+                "",
+                vec![
+                    BbcodeNode::Text("wait".into()).into(),
+                    BbcodeNode::Tag(expected_tag).into(),
+                    BbcodeNode::Text(" for it".into()).into(),
+                ]
+            ))
+        )
+    }
+
+    #[test]
+    fn test_parse_alphanumeric_tag_name() {
+        let input = "[h1]test[/h1]";
+        let expected_tag = BbcodeTag::new("h1").with_text("test");
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
+    #[test]
+    fn test_parse_raw_content() {
+        let input = "[code]let x = [b]1[/b] \\[;[/code]";
+        let expected_tag = BbcodeTag::new("code").with_text("let x = [b]1[/b] \\[;");
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
+    #[test]
+    fn test_parse_noparse() {
+        let input = "[noparse][b]not bold[/b][/noparse][b]bold[/b]";
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok((
+                "",
+                vec![
+                    BbcodeNode::Tag(BbcodeTag::new("noparse").with_text("[b]not bold[/b]")).into(),
+                    BbcodeNode::Tag(BbcodeTag::new("b").with_text("bold")).into(),
+                ]
+            ))
+        )
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let input = "[list][*]one[*]two [b]bold[/b][/list]";
+        let expected_tag = BbcodeTag::new("list")
+            .with_tag(BbcodeTag::new("*").with_text("one"))
+            .with_tag(
+                BbcodeTag::new("*")
+                    .with_text("two ")
+                    .with_tag(BbcodeTag::new("b").with_text("bold")),
+            );
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
+    #[test]
+    fn test_parse_nested_list() {
+        let input = "[list=1][*]one[list][*]nested[/list][*]two[/list]";
+        let expected_tag =
+            BbcodeTag::new("list")
+                .with_simple_param("1")
+                .with_tag(BbcodeTag::new("*").with_text("one").with_tag(
+                    BbcodeTag::new("list").with_tag(BbcodeTag::new("*").with_text("nested")),
+                ))
+                .with_tag(BbcodeTag::new("*").with_text("two"));
+
+        assert_eq!(
+            parse_bbcode(input),
+            Ok(("", vec![BbcodeNode::Tag(expected_tag).into()]))
+        )
+    }
+
     #[test]
     fn test_parse_nested() {
         let input = "[b]test [i]nested[/i][/b]";
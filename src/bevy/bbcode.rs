@@ -2,7 +2,11 @@ use std::sync::Arc;
 
 use bevy::{ecs::system::EntityCommands, prelude::*, utils::HashMap};
 
-use super::color::BbCodeColor;
+use super::{
+    color::BbCodeColor,
+    layer::{TextOutline, TextShadow},
+    theme::BbcodeTheme,
+};
 
 #[derive(Debug, Clone, Component, Default)]
 #[require(Text, BbcodeSettings)]
@@ -30,8 +34,20 @@ pub(crate) struct Modifiers {
 #[derive(Clone, Component)]
 pub struct BbcodeSettings {
     pub font_family: String,
+    /// The font family of `[code]` and `[tt]` tags.
+    ///
+    /// Falls back to the generic monospace family of the font database.
+    pub monospace_font_family: String,
     pub font_size: f32,
     pub color: BbCodeColor,
+    /// Whether text with effects is split into one span per character, see [`BbcodeSettings::with_split_glyphs`].
+    pub split_glyphs: bool,
+    /// The styling of block-level tags like headings.
+    pub theme: BbcodeTheme,
+    /// The outline of all text, see [`BbcodeSettings::with_outline`].
+    pub outline: Option<TextOutline>,
+    /// The drop shadow of all text, see [`BbcodeSettings::with_shadow`].
+    pub shadow: Option<TextShadow>,
 
     pub(crate) modifiers: Modifiers,
 }
@@ -44,12 +60,55 @@ impl BbcodeSettings {
     ) -> Self {
         Self {
             font_family: font_family.into(),
+            monospace_font_family: Default::default(),
             font_size,
             color: color.into(),
+            split_glyphs: false,
+            theme: Default::default(),
+            outline: None,
+            shadow: None,
             modifiers: Default::default(),
         }
     }
 
+    /// Draw an outline around all text, e.g. to make it readable on busy backgrounds.
+    ///
+    /// Can be changed for parts of the text with the `[outline]` tag.
+    pub fn with_outline(mut self, outline: TextOutline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    /// Draw a drop shadow behind all text.
+    ///
+    /// Can be changed for parts of the text with the `[shadow]` tag.
+    pub fn with_shadow(mut self, shadow: TextShadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Use the given theme to style block-level tags like headings.
+    pub fn with_theme(mut self, theme: BbcodeTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Use the given font family for the `[code]` and `[tt]` tags.
+    pub fn with_monospace_font_family<F: Into<String>>(mut self, font_family: F) -> Self {
+        self.monospace_font_family = font_family.into();
+        self
+    }
+
+    /// Split text with effects into one `TextSpan` per character (grapheme).
+    ///
+    /// Each of these spans gets a [`GlyphOffset`](crate::GlyphOffset) component,
+    /// which allows to move the character independently of the rest of the text.
+    /// The effects then animate the characters individually, e.g. the `[rainbow]` tag colors each character differently.
+    pub fn with_split_glyphs(mut self, split_glyphs: bool) -> Self {
+        self.split_glyphs = split_glyphs;
+        self
+    }
+
     /// Register a marker component for the `[m]` tag.
     pub fn with_marker<N: Into<String>, M: Component + Clone>(
         mut self,
@@ -72,7 +131,12 @@ impl Default for BbcodeSettings {
             color: Color::WHITE.into(),
             // TODO: Revisit what to put as default here
             font_family: Default::default(),
+            monospace_font_family: Default::default(),
             font_size: 20.0,
+            split_glyphs: false,
+            theme: Default::default(),
+            outline: None,
+            shadow: None,
             modifiers: Default::default(),
         }
     }
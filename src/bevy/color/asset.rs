@@ -0,0 +1,267 @@
+use std::fmt::Display;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+
+use super::{css_color, parse_color_value, ColorMap, ColorPalette};
+
+/// Named colors and palettes loaded from a file, which are added to the [`ColorMap`] when loaded or modified.
+///
+/// Load it from a `.colors.ron` file (with the `ron` feature) or a `.colors.json` file (with the `json` feature):
+///
+/// ```ron
+/// (
+///     colors: {
+///         "primary": "#4080ff",
+///         "warning": "rgb(255,160,0)",
+///     },
+///     palettes: {
+///         "dark": (colors: { "text": "white" }),
+///         "color_blind": (colors: { "warning": "gold" }, fallback: Some("dark")),
+///     },
+///     active_palette: Some("dark"),
+/// )
+/// ```
+///
+/// Colors can be written in any format supported by the `[c]` tag, including CSS color names.
+/// Make sure to keep the handle alive, so that changes to the file are applied as well.
+#[derive(Debug, Clone, Default, Asset, TypePath)]
+pub struct ColorMapAsset {
+    /// The named colors outside of palettes.
+    pub colors: HashMap<String, Color>,
+    /// The color palettes, by name.
+    pub palettes: HashMap<String, ColorPalette>,
+    /// The palette to activate, if any.
+    pub active_palette: Option<String>,
+}
+
+impl ColorMapAsset {
+    /// Add the colors and palettes to the color map.
+    ///
+    /// Colors which are no longer in the file are kept in the color map.
+    pub fn apply(&self, color_map: &mut ColorMap) {
+        for (name, color) in &self.colors {
+            color_map.insert(name.clone(), *color);
+        }
+
+        for (name, palette) in &self.palettes {
+            color_map.insert_palette(name.clone(), palette.clone());
+        }
+
+        if let Some(active_palette) = &self.active_palette {
+            color_map.set_active_palette(active_palette.clone());
+        }
+    }
+}
+
+/// The file format of a [`ColorMapAsset`].
+#[derive(Debug, Deserialize)]
+struct ColorMapFile {
+    #[serde(default)]
+    colors: HashMap<String, String>,
+    #[serde(default)]
+    palettes: HashMap<String, PaletteFile>,
+    #[serde(default)]
+    active_palette: Option<String>,
+}
+
+/// The file format of a [`ColorPalette`].
+#[derive(Debug, Deserialize)]
+struct PaletteFile {
+    #[serde(default)]
+    colors: HashMap<String, String>,
+    #[serde(default)]
+    fallback: Option<String>,
+}
+
+impl TryFrom<ColorMapFile> for ColorMapAsset {
+    type Error = ColorMapLoaderError;
+
+    fn try_from(file: ColorMapFile) -> Result<Self, Self::Error> {
+        let palettes = file
+            .palettes
+            .into_iter()
+            .map(|(name, palette_file)| {
+                let mut palette = ColorPalette::new();
+
+                for (color_name, color) in parse_colors(palette_file.colors)? {
+                    palette = palette.with_color(color_name, color);
+                }
+
+                if let Some(fallback) = palette_file.fallback {
+                    palette = palette.with_fallback(fallback);
+                }
+
+                Ok((name, palette))
+            })
+            .collect::<Result<_, Self::Error>>()?;
+
+        Ok(Self {
+            colors: parse_colors(file.colors)?,
+            palettes,
+            active_palette: file.active_palette,
+        })
+    }
+}
+
+/// Parse the color values of the file.
+fn parse_colors(
+    colors: HashMap<String, String>,
+) -> Result<HashMap<String, Color>, ColorMapLoaderError> {
+    colors
+        .into_iter()
+        .map(
+            |(name, value)| match parse_color_value(&value).or_else(|| css_color(&value)) {
+                Some(color) => Ok((name, color)),
+                None => Err(ColorMapLoaderError::InvalidColor { name, value }),
+            },
+        )
+        .collect()
+}
+
+/// Loads [`ColorMapAsset`]s from `.colors.ron` and `.colors.json` files.
+#[derive(Debug, Default)]
+pub struct ColorMapLoader;
+
+/// An error while loading a [`ColorMapAsset`].
+#[derive(Debug)]
+pub enum ColorMapLoaderError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not valid RON.
+    #[cfg(feature = "ron")]
+    Ron(ron::error::SpannedError),
+    /// The file is not valid JSON.
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    /// The file extension is not supported.
+    UnsupportedFormat,
+    /// A color value could not be parsed.
+    InvalidColor { name: String, value: String },
+}
+
+impl Display for ColorMapLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Could not read the color file: {error}"),
+            #[cfg(feature = "ron")]
+            Self::Ron(error) => write!(f, "Could not parse the RON color file: {error}"),
+            #[cfg(feature = "json")]
+            Self::Json(error) => write!(f, "Could not parse the JSON color file: {error}"),
+            Self::UnsupportedFormat => write!(f, "Unsupported color file format"),
+            Self::InvalidColor { name, value } => {
+                write!(f, "Invalid value {value} for color {name}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ColorMapLoaderError {}
+
+impl From<std::io::Error> for ColorMapLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl AssetLoader for ColorMapLoader {
+    type Asset = ColorMapAsset;
+    type Settings = ();
+    type Error = ColorMapLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<ColorMapAsset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let extension = load_context
+            .path()
+            .extension()
+            .and_then(|extension| extension.to_str());
+
+        let file: ColorMapFile = match extension {
+            #[cfg(feature = "ron")]
+            Some("ron") => ron::de::from_bytes(&bytes).map_err(ColorMapLoaderError::Ron)?,
+            #[cfg(feature = "json")]
+            Some("json") => serde_json::from_slice(&bytes).map_err(ColorMapLoaderError::Json)?,
+            _ => return Err(ColorMapLoaderError::UnsupportedFormat),
+        };
+
+        file.try_into()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[
+            #[cfg(feature = "ron")]
+            "colors.ron",
+            #[cfg(feature = "json")]
+            "colors.json",
+        ]
+    }
+}
+
+/// Add the colors of loaded and modified color files to the color map.
+pub(super) fn apply_color_map_assets(
+    mut color_map: ResMut<ColorMap>,
+    mut asset_events: EventReader<AssetEvent<ColorMapAsset>>,
+    assets: Res<Assets<ColorMapAsset>>,
+) {
+    for event in asset_events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                if let Some(asset) = assets.get(id) {
+                    asset.apply(&mut color_map);
+                }
+            }
+            AssetEvent::Removed { id: _ }
+            | AssetEvent::Unused { id: _ }
+            | AssetEvent::LoadedWithDependencies { id: _ } => {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ron"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ron_file() {
+        let file: ColorMapFile = ron::de::from_str(
+            r##"(
+                colors: { "primary": "#ff0000" },
+                palettes: {
+                    "dark": (colors: { "text": "white" }),
+                    "color_blind": (colors: { "primary": "rgb(0,0,255)" }, fallback: Some("dark")),
+                },
+                active_palette: Some("color_blind"),
+            )"##,
+        )
+        .unwrap();
+        let asset = ColorMapAsset::try_from(file).unwrap();
+
+        let mut color_map = ColorMap::default();
+        asset.apply(&mut color_map);
+
+        assert_eq!(color_map.active_palette(), Some("color_blind"));
+        assert_eq!(color_map.get("primary"), Some(Color::srgb(0., 0., 1.)));
+        assert_eq!(color_map.get("text"), Some(Color::srgb(1., 1., 1.)));
+    }
+
+    #[test]
+    fn invalid_color_value() {
+        let file: ColorMapFile = ron::de::from_str(r#"(colors: { "primary": "nope" })"#).unwrap();
+
+        assert!(matches!(
+            ColorMapAsset::try_from(file),
+            Err(ColorMapLoaderError::InvalidColor { .. })
+        ));
+    }
+}
@@ -0,0 +1,624 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{highlight::TextBackground, plugin::BbcodeSet};
+
+#[cfg(any(feature = "ron", feature = "json"))]
+mod asset;
+mod modifier;
+mod overrides;
+mod palette;
+mod parse;
+mod transition;
+
+#[cfg(any(feature = "ron", feature = "json"))]
+pub use asset::{ColorMapAsset, ColorMapLoader, ColorMapLoaderError};
+pub(crate) use modifier::apply_modifiers;
+pub use modifier::ColorModifier;
+pub use overrides::BbcodeColorOverrides;
+pub(crate) use overrides::ColorOverrides;
+pub use palette::ColorPalette;
+pub(crate) use parse::{css_color, is_color_value, parse_color_value};
+pub use transition::ColorTransition;
+use transition::{animate_color_transitions, TextColorTransition};
+
+pub struct ColorPlugin;
+
+impl Plugin for ColorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColorMap>()
+            .add_event::<ColorMapChanged>()
+            .add_systems(
+                Update,
+                (send_color_changes, update_colors, animate_color_transitions)
+                    .chain()
+                    .in_set(BbcodeSet::Colors),
+            );
+
+        #[cfg(any(feature = "ron", feature = "json"))]
+        app.init_asset::<ColorMapAsset>()
+            .init_asset_loader::<ColorMapLoader>()
+            .add_systems(
+                Update,
+                asset::apply_color_map_assets
+                    .in_set(BbcodeSet::Colors)
+                    .before(send_color_changes),
+            );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BbCodeColor {
+    Named(String),
+    Static(Color),
+}
+
+impl BbCodeColor {
+    pub fn to_color(&self, color_map: &ColorMap) -> Option<Color> {
+        self.resolve(ColorScope::new(color_map, None))
+    }
+
+    /// Get the color, looking up names in the given scope.
+    pub(crate) fn resolve(&self, scope: ColorScope) -> Option<Color> {
+        match self {
+            Self::Static(color) => Some(*color),
+            Self::Named(name) => named_color(name, scope),
+        }
+    }
+}
+
+/// The named colors available to a text.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ColorScope<'a> {
+    color_map: &'a ColorMap,
+    /// The overrides of the closest entity, which take precedence over the color map.
+    overrides: Option<&'a BbcodeColorOverrides>,
+}
+
+impl<'a> ColorScope<'a> {
+    pub(crate) fn new(
+        color_map: &'a ColorMap,
+        overrides: Option<&'a BbcodeColorOverrides>,
+    ) -> Self {
+        Self {
+            color_map,
+            overrides,
+        }
+    }
+
+    /// Get the registered color for the given name.
+    fn get(&self, name: &str) -> Option<Color> {
+        self.overrides
+            .and_then(|overrides| overrides.get(name))
+            .or_else(|| self.color_map.get(name))
+    }
+}
+
+/// Get the color with the given name.
+///
+/// Registered names take precedence over the CSS colors.
+fn named_color(name: &str, scope: ColorScope) -> Option<Color> {
+    scope.get(name).or_else(|| css_color(name))
+}
+
+/// Get the first color of the chain which can be resolved.
+pub(crate) fn resolve_color_chain<'a>(
+    chain: impl IntoIterator<Item = &'a BbCodeColor>,
+    scope: ColorScope,
+) -> Option<Color> {
+    chain.into_iter().find_map(|color| color.resolve(scope))
+}
+
+impl From<Color> for BbCodeColor {
+    fn from(value: Color) -> Self {
+        Self::Static(value)
+    }
+}
+
+impl From<String> for BbCodeColor {
+    fn from(value: String) -> Self {
+        Self::Named(value)
+    }
+}
+
+#[derive(Debug, Resource, Default)]
+pub struct ColorMap {
+    /// The map from name to color.
+    ///
+    /// Used for the names which are not defined in the active palette.
+    map: HashMap<String, Color>,
+
+    /// The color palettes, by name.
+    palettes: HashMap<String, ColorPalette>,
+
+    /// The name of the palette to look up colors in first.
+    active_palette: Option<String>,
+
+    /// The names of the colors changed since the last [`ColorMapChanged`] events were sent,
+    /// with the transition to the new color.
+    ///
+    /// Used to only update what's needed.
+    changes: HashMap<String, Option<ColorTransition>>,
+}
+
+impl ColorMap {
+    /// Insert (add or update) a new named color.
+    ///
+    /// Returns `&mut self` for chaining.
+    pub fn insert<N, C>(&mut self, name: N, color: C) -> &mut Self
+    where
+        N: Into<String>,
+        C: Into<Color>,
+    {
+        let name = name.into();
+        self.map.insert(name.clone(), color.into());
+        self.changes.insert(name, None);
+        self
+    }
+
+    /// Insert (add or update) a new named color and gradually change the text to the new color.
+    ///
+    /// Returns `&mut self` for chaining.
+    pub fn insert_with_transition<N, C>(
+        &mut self,
+        name: N,
+        color: C,
+        transition: ColorTransition,
+    ) -> &mut Self
+    where
+        N: Into<String>,
+        C: Into<Color>,
+    {
+        let name = name.into();
+        self.map.insert(name.clone(), color.into());
+        self.changes.insert(name, Some(transition));
+        self
+    }
+
+    /// Get the color for the given name.
+    ///
+    /// The color is looked up in the active palette and its fallbacks first.
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.active_palettes()
+            .iter()
+            .find_map(|palette| palette.get(name))
+            .or_else(|| self.map.get(name).copied())
+    }
+
+    /// Insert (add or replace) a color palette with the given name.
+    ///
+    /// Returns `&mut self` for chaining.
+    pub fn insert_palette<N: Into<String>>(&mut self, name: N, palette: ColorPalette) -> &mut Self {
+        let name = name.into();
+
+        // Replacing a palette might change the fallback chain
+        let old_names = self.active_color_names();
+        self.palettes.insert(name, palette);
+        self.mark_updated(old_names, None);
+
+        self
+    }
+
+    /// Get the color palette with the given name.
+    pub fn palette(&self, name: &str) -> Option<&ColorPalette> {
+        self.palettes.get(name)
+    }
+
+    /// Look up the colors in the palette with the given name first.
+    ///
+    /// All text using the colors of the old and new palette is updated.
+    ///
+    /// Returns `&mut self` for chaining.
+    pub fn set_active_palette<N: Into<String>>(&mut self, name: N) -> &mut Self {
+        self.switch_palette(Some(name.into()), None)
+    }
+
+    /// Look up the colors in the palette with the given name first and gradually change the text to the new colors.
+    ///
+    /// Returns `&mut self` for chaining.
+    pub fn set_active_palette_with_transition<N: Into<String>>(
+        &mut self,
+        name: N,
+        transition: ColorTransition,
+    ) -> &mut Self {
+        self.switch_palette(Some(name.into()), Some(transition))
+    }
+
+    /// Only use the colors outside of palettes.
+    ///
+    /// Returns `&mut self` for chaining.
+    pub fn clear_active_palette(&mut self) -> &mut Self {
+        self.switch_palette(None, None)
+    }
+
+    /// The name of the palette to look up colors in first.
+    pub fn active_palette(&self) -> Option<&str> {
+        self.active_palette.as_deref()
+    }
+
+    fn switch_palette(
+        &mut self,
+        name: Option<String>,
+        transition: Option<ColorTransition>,
+    ) -> &mut Self {
+        let old_names = self.active_color_names();
+        self.active_palette = name;
+        self.mark_updated(old_names, transition);
+
+        self
+    }
+
+    /// The active palette followed by its fallback palettes.
+    fn active_palettes(&self) -> Vec<&ColorPalette> {
+        let mut palettes: Vec<&ColorPalette> = Vec::new();
+        let mut next = self.active_palette.as_deref();
+
+        while let Some(palette) = next.and_then(|name| self.palettes.get(name)) {
+            // Stop at cyclic fallbacks
+            if palettes.iter().any(|other| std::ptr::eq(*other, palette)) {
+                break;
+            }
+
+            palettes.push(palette);
+            next = palette.fallback();
+        }
+
+        palettes
+    }
+
+    /// The names of all colors defined in the active palettes.
+    fn active_color_names(&self) -> Vec<String> {
+        self.active_palettes()
+            .iter()
+            .flat_map(|palette| palette.names())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// Mark the given names and the names of the active palettes as updated.
+    fn mark_updated(&mut self, names: Vec<String>, transition: Option<ColorTransition>) {
+        let changes = names
+            .into_iter()
+            .chain(self.active_color_names())
+            .map(|name| (name, transition));
+        self.changes.extend(changes);
+    }
+
+    /// Take the changes since the last call, to send them as events.
+    fn take_changes(&mut self) -> Vec<ColorMapChanged> {
+        self.changes
+            .drain()
+            .map(|(name, transition)| ColorMapChanged { name, transition })
+            .collect()
+    }
+}
+
+/// Sent when a named color of the [`ColorMap`] has changed, e.g. by inserting it or by switching the palette.
+///
+/// The events are sent in [`BbcodeSet::Colors`], read them after the set to react to the changes in the same frame.
+/// Changes made after the set are sent in the next frame.
+#[derive(Debug, Clone, PartialEq, Event)]
+pub struct ColorMapChanged {
+    /// The name of the changed color, it might not be defined anymore.
+    pub name: String,
+    /// The transition to the new color, if it shouldn't change instantly.
+    pub transition: Option<ColorTransition>,
+}
+
+/// The names of the colors changed in this frame, with the transition to the new color.
+struct ColorChanges<'a>(HashMap<&'a str, Option<ColorTransition>>);
+
+impl<'a> ColorChanges<'a> {
+    fn new(events: impl Iterator<Item = &'a ColorMapChanged>) -> Self {
+        Self(
+            events
+                .map(|event| (event.name.as_str(), event.transition))
+                .collect(),
+        )
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Determine if any of the colors with the given names has changed.
+    fn contains_any<'n>(&self, mut names: impl Iterator<Item = &'n str>) -> bool {
+        names.any(|name| self.0.contains_key(name))
+    }
+
+    /// The transition of the first changed color with the given names that should change gradually.
+    fn transition<'n>(&self, mut names: impl Iterator<Item = &'n str>) -> Option<ColorTransition> {
+        names.find_map(|name| self.0.get(name).copied().flatten())
+    }
+}
+
+/// Tracker for text that's colored via named BBCode components.
+#[derive(Debug, Component)]
+pub struct BbCodeColored {
+    pub name: String,
+    /// The inherited colors to use while the name is not registered in the [`ColorMap`], innermost first.
+    ///
+    /// E.g. the color of the surrounding `[c]` tag or the default color of the text.
+    pub fallbacks: Vec<BbCodeColor>,
+    /// The modifiers applied to the resolved color, e.g. via `[c=primary alpha=0.5]`.
+    pub modifiers: Vec<ColorModifier>,
+}
+
+impl BbCodeColored {
+    /// Resolve the color, using the first fallback that can be resolved if the name isn't registered.
+    pub(crate) fn resolve(&self, scope: ColorScope) -> Option<Color> {
+        named_color(&self.name, scope)
+            .or_else(|| resolve_color_chain(&self.fallbacks, scope))
+            .map(|color| apply_modifiers(color, &self.modifiers))
+    }
+
+    /// The name followed by the fallback names.
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(color_names(&self.fallbacks))
+    }
+}
+
+/// Tracker for the characters of a `[gradient]` tag, colored between two (named) colors.
+#[derive(Debug, Component)]
+pub struct BbCodeGradientColored {
+    /// The color at the start of the gradient.
+    pub from: BbCodeColor,
+    /// The color at the end of the gradient.
+    pub to: BbCodeColor,
+    /// The position of the character in the gradient, between `0` and `1`.
+    pub position: f32,
+    /// The inherited colors to use while a color of the gradient is not registered, innermost first.
+    pub fallbacks: Vec<BbCodeColor>,
+    /// The modifiers applied to the resolved color.
+    pub modifiers: Vec<ColorModifier>,
+}
+
+impl BbCodeGradientColored {
+    /// Resolve the color at the position of the character.
+    pub(crate) fn resolve(&self, scope: ColorScope) -> Option<Color> {
+        let resolve = |color: &BbCodeColor| {
+            resolve_color_chain(std::iter::once(color).chain(&self.fallbacks), scope)
+        };
+        let color = resolve(&self.from)?.mix(&resolve(&self.to)?, self.position);
+
+        Some(apply_modifiers(color, &self.modifiers))
+    }
+
+    /// The names of the colors of the gradient followed by the fallback names.
+    fn names(&self) -> impl Iterator<Item = &str> {
+        color_names([&self.from, &self.to].into_iter().chain(&self.fallbacks))
+    }
+}
+
+/// The names of the named colors.
+fn color_names<'a>(
+    colors: impl IntoIterator<Item = &'a BbCodeColor>,
+) -> impl Iterator<Item = &'a str> {
+    colors.into_iter().filter_map(|color| match color {
+        BbCodeColor::Named(name) => Some(name.as_str()),
+        BbCodeColor::Static(_) => None,
+    })
+}
+
+/// Tracker for text backgrounds that are colored via named BBCode colors.
+#[derive(Debug, Component)]
+pub struct BbCodeBackgroundColored {
+    pub name: String,
+}
+
+/// Send the changes of the color map as events.
+fn send_color_changes(
+    mut color_map: ResMut<ColorMap>,
+    mut color_changed_events: EventWriter<ColorMapChanged>,
+) {
+    if color_map.is_changed() {
+        // Don't mark the map as changed again, it's only changed by actual changes
+        color_changed_events.send_batch(color_map.bypass_change_detection().take_changes());
+    }
+}
+
+/// Update all colors whose name or overrides have changed.
+fn update_colors(
+    mut commands: Commands,
+    color_map: Res<ColorMap>,
+    mut color_changed_events: EventReader<ColorMapChanged>,
+    mut overrides: ColorOverrides,
+    mut colored_text_query: Query<(Entity, &BbCodeColored, &mut TextColor)>,
+    mut gradient_text_query: Query<
+        (Entity, &BbCodeGradientColored, &mut TextColor),
+        Without<BbCodeColored>,
+    >,
+    mut colored_background_query: Query<(Entity, &BbCodeBackgroundColored, &mut TextBackground)>,
+) {
+    let changes = ColorChanges::new(color_changed_events.read());
+    // The text below changed overrides needs to be updated entirely
+    let changed_scopes = overrides.changed_scopes();
+
+    if changes.is_empty() && changed_scopes.is_empty() {
+        return;
+    }
+
+    for (entity, colored, mut text_color) in colored_text_query.iter_mut() {
+        // A fallback color might be used until the name is registered
+        if !changes.contains_any(colored.names()) && !changed_scopes.contains(&entity) {
+            continue;
+        }
+
+        let scope = ColorScope::new(&color_map, overrides.get(entity));
+        if let Some(color) = colored.resolve(scope) {
+            let transition = changes.transition(colored.names());
+            set_text_color(&mut commands, entity, &mut text_color, color, transition);
+        }
+    }
+
+    for (entity, colored, mut text_color) in gradient_text_query.iter_mut() {
+        if !changes.contains_any(colored.names()) && !changed_scopes.contains(&entity) {
+            continue;
+        }
+
+        let scope = ColorScope::new(&color_map, overrides.get(entity));
+        if let Some(color) = colored.resolve(scope) {
+            let transition = changes.transition(colored.names());
+            set_text_color(&mut commands, entity, &mut text_color, color, transition);
+        }
+    }
+
+    for (entity, colored, mut background) in colored_background_query.iter_mut() {
+        if changes.contains_any(std::iter::once(colored.name.as_str()))
+            || changed_scopes.contains(&entity)
+        {
+            let scope = ColorScope::new(&color_map, overrides.get(entity));
+            background.0 = named_color(&colored.name, scope).unwrap_or(Color::NONE);
+        }
+    }
+}
+
+/// Change the color of the text, either instantly or with the given transition.
+fn set_text_color(
+    commands: &mut Commands,
+    entity: Entity,
+    text_color: &mut TextColor,
+    color: Color,
+    transition: Option<ColorTransition>,
+) {
+    match transition {
+        Some(transition) if transition.duration > 0. => {
+            // Start from the current color, which might be in the middle of another transition
+            commands.entity(entity).insert(TextColorTransition::new(
+                text_color.0,
+                color,
+                transition,
+            ));
+        }
+        _ => {
+            text_color.0 = color;
+            commands.entity(entity).remove::<TextColorTransition>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+
+    use super::*;
+
+    const RED: Color = Color::srgb(1., 0., 0.);
+    const GREEN: Color = Color::srgb(0., 1., 0.);
+    const BLUE: Color = Color::srgb(0., 0., 1.);
+
+    #[test]
+    fn active_palette_with_fallbacks() {
+        let mut color_map = ColorMap::default();
+        color_map
+            .insert("text", RED)
+            .insert("accent", RED)
+            .insert_palette("dark", ColorPalette::new().with_color("accent", GREEN))
+            .insert_palette(
+                "color_blind",
+                ColorPalette::new()
+                    .with_color("text", BLUE)
+                    .with_fallback("dark"),
+            );
+
+        assert_eq!(color_map.get("accent"), Some(RED));
+
+        color_map.set_active_palette("color_blind");
+        assert_eq!(color_map.get("text"), Some(BLUE));
+        assert_eq!(color_map.get("accent"), Some(GREEN));
+
+        color_map.clear_active_palette();
+        assert_eq!(color_map.get("text"), Some(RED));
+    }
+
+    #[test]
+    fn switching_palettes_marks_colors_as_changed() {
+        let changed_names = |color_map: &mut ColorMap| -> HashSet<String> {
+            color_map
+                .take_changes()
+                .into_iter()
+                .map(|change| change.name)
+                .collect()
+        };
+
+        let mut color_map = ColorMap::default();
+        color_map
+            .insert_palette("light", ColorPalette::new().with_color("text", BLUE))
+            .insert_palette("dark", ColorPalette::new().with_color("accent", GREEN));
+        color_map.take_changes();
+
+        color_map.set_active_palette("light");
+        assert_eq!(
+            changed_names(&mut color_map),
+            HashSet::from(["text".into()])
+        );
+        assert_eq!(color_map.get("text"), Some(BLUE));
+
+        // Colors of the old palette are changed as well, as they might not be defined anymore
+        color_map.set_active_palette("dark");
+        assert_eq!(
+            changed_names(&mut color_map),
+            HashSet::from(["text".into(), "accent".into()])
+        );
+        assert_eq!(color_map.get("text"), None);
+        assert_eq!(color_map.get("accent"), Some(GREEN));
+    }
+
+    #[test]
+    fn changes_are_sent_as_events() {
+        #[derive(Default, Resource)]
+        struct ReceivedChanges(Vec<ColorMapChanged>);
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ColorMap>()
+            .init_resource::<ReceivedChanges>()
+            .add_event::<ColorMapChanged>()
+            .add_systems(
+                Update,
+                (
+                    send_color_changes,
+                    |mut events: EventReader<ColorMapChanged>,
+                     mut received: ResMut<ReceivedChanges>| {
+                        received.0.extend(events.read().cloned());
+                    },
+                )
+                    .chain(),
+            );
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("text", RED)
+            .insert_with_transition("accent", GREEN, ColorTransition::new(1.));
+        app.update();
+        // Sending the events doesn't mark the map as changed again
+        app.update();
+
+        let changes: HashMap<String, Option<ColorTransition>> = app
+            .world()
+            .resource::<ReceivedChanges>()
+            .0
+            .iter()
+            .map(|change| (change.name.clone(), change.transition))
+            .collect();
+        assert_eq!(app.world().resource::<ReceivedChanges>().0.len(), 2);
+        assert_eq!(
+            changes,
+            HashMap::from([
+                ("text".into(), None),
+                ("accent".into(), Some(ColorTransition::new(1.)))
+            ])
+        );
+    }
+
+    #[test]
+    fn cyclic_palette_fallbacks() {
+        let mut color_map = ColorMap::default();
+        color_map
+            .insert_palette("a", ColorPalette::new().with_fallback("b"))
+            .insert_palette("b", ColorPalette::new().with_fallback("a"))
+            .set_active_palette("a");
+
+        assert_eq!(color_map.get("text"), None);
+    }
+}
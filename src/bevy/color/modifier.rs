@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+/// A change applied to a resolved color, e.g. via `[c=primary alpha=0.5]` or `[fade=0.5]`.
+///
+/// Named colors keep their modifiers when they are updated in the [`ColorMap`](crate::ColorMap).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorModifier {
+    /// Replace the alpha value of the color.
+    Alpha(f32),
+    /// Multiply the alpha value of the color.
+    Fade(f32),
+    /// Increase the luminance of the color by the given amount between `0` and `1`.
+    Lighten(f32),
+    /// Decrease the luminance of the color by the given amount between `0` and `1`.
+    Darken(f32),
+}
+
+impl ColorModifier {
+    /// Apply the modifier to the given color.
+    pub fn apply(&self, color: Color) -> Color {
+        match *self {
+            Self::Alpha(alpha) => color.with_alpha(alpha),
+            Self::Fade(factor) => color.with_alpha(color.alpha() * factor),
+            Self::Lighten(amount) => color.lighter(amount),
+            Self::Darken(amount) => color.darker(amount),
+        }
+    }
+}
+
+/// Apply the modifiers to the color, in order.
+pub(crate) fn apply_modifiers(color: Color, modifiers: &[ColorModifier]) -> Color {
+    modifiers
+        .iter()
+        .fold(color, |color, modifier| modifier.apply(color))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_are_applied_in_order() {
+        let color = Color::srgba(1., 0., 0., 0.8);
+
+        assert_eq!(
+            apply_modifiers(
+                color,
+                &[ColorModifier::Alpha(0.5), ColorModifier::Fade(0.5)]
+            )
+            .alpha(),
+            0.25
+        );
+        assert_eq!(
+            apply_modifiers(
+                color,
+                &[ColorModifier::Fade(0.5), ColorModifier::Alpha(0.5)]
+            )
+            .alpha(),
+            0.5
+        );
+        assert_eq!(
+            ColorModifier::Lighten(1.).apply(color).to_srgba(),
+            Color::WHITE.with_alpha(0.8).to_srgba()
+        );
+        assert_eq!(
+            ColorModifier::Darken(1.).apply(color).luminance(),
+            Color::BLACK.luminance()
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+/// Named colors for the text of a [`Bbcode`](crate::Bbcode) entity, which take precedence over the [`ColorMap`](crate::ColorMap).
+///
+/// Insert it on the `Bbcode` entity or one of its ancestors, e.g. to use different team colors per player card.
+/// The closest overrides are used, the names which are not overridden are looked up in the `ColorMap`.
+/// Changing the overrides updates the colors of the text below the entity.
+#[derive(Debug, Clone, Default, Component)]
+pub struct BbcodeColorOverrides {
+    /// The map from name to color.
+    colors: HashMap<String, Color>,
+}
+
+impl BbcodeColorOverrides {
+    /// Create new, empty overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the color with the given name.
+    pub fn with_color<N, C>(mut self, name: N, color: C) -> Self
+    where
+        N: Into<String>,
+        C: Into<Color>,
+    {
+        self.insert(name, color);
+        self
+    }
+
+    /// Insert (add or update) an overridden color.
+    ///
+    /// Returns `&mut self` for chaining.
+    pub fn insert<N, C>(&mut self, name: N, color: C) -> &mut Self
+    where
+        N: Into<String>,
+        C: Into<Color>,
+    {
+        self.colors.insert(name.into(), color.into());
+        self
+    }
+
+    /// Stop overriding the color with the given name.
+    pub fn remove(&mut self, name: &str) -> Option<Color> {
+        self.colors.remove(name)
+    }
+
+    /// Get the overridden color for the given name.
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+}
+
+/// Finds the [`BbcodeColorOverrides`] that apply to the text.
+#[derive(SystemParam)]
+pub(crate) struct ColorOverrides<'w, 's> {
+    parent_query: Query<'w, 's, &'static Parent>,
+    children_query: Query<'w, 's, &'static Children>,
+    overrides_query: Query<'w, 's, &'static BbcodeColorOverrides>,
+    changed_overrides_query: Query<'w, 's, Entity, Changed<BbcodeColorOverrides>>,
+    removed_overrides: RemovedComponents<'w, 's, BbcodeColorOverrides>,
+}
+
+impl ColorOverrides<'_, '_> {
+    /// Get the overrides of the closest entity, starting with the entity itself.
+    pub(crate) fn get(&self, entity: Entity) -> Option<&BbcodeColorOverrides> {
+        std::iter::once(entity)
+            .chain(self.parent_query.iter_ancestors(entity))
+            .find_map(|entity| self.overrides_query.get(entity).ok())
+    }
+
+    /// The entities whose overrides have been added, changed or removed, including their descendants.
+    pub(crate) fn changed_scopes(&mut self) -> HashSet<Entity> {
+        let roots: Vec<Entity> = self
+            .changed_overrides_query
+            .iter()
+            .chain(self.removed_overrides.read())
+            .collect();
+
+        roots
+            .into_iter()
+            .flat_map(|root| {
+                std::iter::once(root).chain(self.children_query.iter_descendants(root))
+            })
+            .collect()
+    }
+}
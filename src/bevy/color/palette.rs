@@ -0,0 +1,53 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// A set of named colors, e.g. for a light, dark or color-blind UI theme.
+///
+/// Register it via [`ColorMap::insert_palette`](crate::ColorMap::insert_palette)
+/// and switch between palettes with [`ColorMap::set_active_palette`](crate::ColorMap::set_active_palette).
+#[derive(Debug, Clone, Default)]
+pub struct ColorPalette {
+    /// The map from name to color.
+    colors: HashMap<String, Color>,
+    /// The name of the palette to look up the colors missing in this palette.
+    fallback: Option<String>,
+}
+
+impl ColorPalette {
+    /// Create a new, empty palette.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the named color to the palette.
+    pub fn with_color<N, C>(mut self, name: N, color: C) -> Self
+    where
+        N: Into<String>,
+        C: Into<Color>,
+    {
+        self.colors.insert(name.into(), color.into());
+        self
+    }
+
+    /// Look up the colors missing in this palette in the palette with the given name.
+    ///
+    /// E.g. a color-blind palette can fall back to the dark palette and only override a few colors.
+    pub fn with_fallback<N: Into<String>>(mut self, palette_name: N) -> Self {
+        self.fallback = Some(palette_name.into());
+        self
+    }
+
+    /// Get the color for the given name, without considering the fallback palette.
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+
+    /// The name of the palette to look up the colors missing in this palette.
+    pub fn fallback(&self) -> Option<&str> {
+        self.fallback.as_deref()
+    }
+
+    /// The names of the colors in this palette.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.colors.keys().map(String::as_str)
+    }
+}
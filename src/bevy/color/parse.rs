@@ -0,0 +1,280 @@
+use bevy::{color::palettes::css, prelude::*};
+
+/// Parse a color value, e.g. `#ff0000`, `#f008`, `rgb(255,0,0)`, `rgba(255,0,0,0.5)` or `hsl(0,100%,50%)`.
+///
+/// Returns `None` if the value is not in any of these formats.
+pub(crate) fn parse_color_value(value: &str) -> Option<Color> {
+    let value = value.trim();
+
+    if value.starts_with('#') {
+        return Srgba::hex(value).ok().map(Color::from);
+    }
+
+    parse_color_function(value)
+}
+
+/// Whether the value is meant as a color value instead of a color name,
+/// i.e. it's a hex color or a color function.
+pub(crate) fn is_color_value(value: &str) -> bool {
+    let value = value.trim();
+    value.starts_with('#') || value.contains('(')
+}
+
+/// Parse the `rgb()`, `rgba()`, `hsl()` and `hsla()` color functions.
+///
+/// The arguments can be separated by commas or whitespace, the alpha value can also be separated by `/`.
+fn parse_color_function(value: &str) -> Option<Color> {
+    let (name, args) = value.strip_suffix(')')?.split_once('(')?;
+    let args: Vec<&str> = args
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+
+    let alpha = match args.len() {
+        3 => 1.,
+        4 => parse_unit(args[3], 1.)?,
+        _ => return None,
+    };
+
+    match name.trim().to_ascii_lowercase().as_str() {
+        "rgb" | "rgba" => Some(
+            Srgba::new(
+                parse_unit(args[0], 255.)?,
+                parse_unit(args[1], 255.)?,
+                parse_unit(args[2], 255.)?,
+                alpha,
+            )
+            .into(),
+        ),
+        "hsl" | "hsla" => Some(
+            Hsla::new(
+                args[0]
+                    .strip_suffix("deg")
+                    .unwrap_or(args[0])
+                    .parse()
+                    .ok()?,
+                parse_unit(args[1], 100.)?,
+                parse_unit(args[2], 100.)?,
+                alpha,
+            )
+            .into(),
+        ),
+        _ => None,
+    }
+}
+
+/// Parse a color channel into the range `[0, 1]`, either as percentage or as number with the given maximum.
+fn parse_unit(value: &str, max: f32) -> Option<f32> {
+    let value = match value.strip_suffix('%') {
+        Some(percentage) => percentage.parse::<f32>().ok()? / 100.,
+        None => value.parse::<f32>().ok()? / max,
+    };
+
+    Some(value.clamp(0., 1.))
+}
+
+/// Get the CSS color with the given name, e.g. `red` or `cornflowerblue`.
+pub(crate) fn css_color(name: &str) -> Option<Color> {
+    let name = name
+        .trim()
+        .to_ascii_lowercase()
+        .replace(['_', '-', ' '], "");
+
+    let color = match name.as_str() {
+        "aliceblue" => css::ALICE_BLUE,
+        "antiquewhite" => css::ANTIQUE_WHITE,
+        "aqua" => css::AQUA,
+        "aquamarine" => css::AQUAMARINE,
+        "azure" => css::AZURE,
+        "beige" => css::BEIGE,
+        "bisque" => css::BISQUE,
+        "black" => css::BLACK,
+        "blanchedalmond" => css::BLANCHED_ALMOND,
+        "blue" => css::BLUE,
+        "blueviolet" => css::BLUE_VIOLET,
+        "brown" => css::BROWN,
+        "burlywood" => css::BURLYWOOD,
+        "cadetblue" => css::CADET_BLUE,
+        "chartreuse" => css::CHARTREUSE,
+        "chocolate" => css::CHOCOLATE,
+        "coral" => css::CORAL,
+        "cornflowerblue" => css::CORNFLOWER_BLUE,
+        "cornsilk" => css::CORNSILK,
+        "crimson" => css::CRIMSON,
+        "cyan" => css::AQUA,
+        "darkblue" => css::DARK_BLUE,
+        "darkcyan" => css::DARK_CYAN,
+        "darkgoldenrod" => css::DARK_GOLDENROD,
+        "darkgray" => css::DARK_GRAY,
+        "darkgreen" => css::DARK_GREEN,
+        "darkgrey" => css::DARK_GREY,
+        "darkkhaki" => css::DARK_KHAKI,
+        "darkmagenta" => css::DARK_MAGENTA,
+        "darkolivegreen" => css::DARK_OLIVEGREEN,
+        "darkorange" => css::DARK_ORANGE,
+        "darkorchid" => css::DARK_ORCHID,
+        "darkred" => css::DARK_RED,
+        "darksalmon" => css::DARK_SALMON,
+        "darkseagreen" => css::DARK_SEA_GREEN,
+        "darkslateblue" => css::DARK_SLATE_BLUE,
+        "darkslategray" => css::DARK_SLATE_GRAY,
+        "darkslategrey" => css::DARK_SLATE_GREY,
+        "darkturquoise" => css::DARK_TURQUOISE,
+        "darkviolet" => css::DARK_VIOLET,
+        "deeppink" => css::DEEP_PINK,
+        "deepskyblue" => css::DEEP_SKY_BLUE,
+        "dimgray" => css::DIM_GRAY,
+        "dimgrey" => css::DIM_GREY,
+        "dodgerblue" => css::DODGER_BLUE,
+        "firebrick" => css::FIRE_BRICK,
+        "floralwhite" => css::FLORAL_WHITE,
+        "forestgreen" => css::FOREST_GREEN,
+        "fuchsia" => css::FUCHSIA,
+        "gainsboro" => css::GAINSBORO,
+        "ghostwhite" => css::GHOST_WHITE,
+        "gold" => css::GOLD,
+        "goldenrod" => css::GOLDENROD,
+        "gray" => css::GRAY,
+        "green" => css::GREEN,
+        "greenyellow" => css::GREEN_YELLOW,
+        "grey" => css::GREY,
+        "honeydew" => css::HONEYDEW,
+        "hotpink" => css::HOT_PINK,
+        "indianred" => css::INDIAN_RED,
+        "indigo" => css::INDIGO,
+        "ivory" => css::IVORY,
+        "khaki" => css::KHAKI,
+        "lavender" => css::LAVENDER,
+        "lavenderblush" => css::LAVENDER_BLUSH,
+        "lawngreen" => css::LAWN_GREEN,
+        "lemonchiffon" => css::LEMON_CHIFFON,
+        "lightblue" => css::LIGHT_BLUE,
+        "lightcoral" => css::LIGHT_CORAL,
+        "lightcyan" => css::LIGHT_CYAN,
+        "lightgoldenrodyellow" => css::LIGHT_GOLDENROD_YELLOW,
+        "lightgray" => css::LIGHT_GRAY,
+        "lightgreen" => css::LIGHT_GREEN,
+        "lightgrey" => css::LIGHT_GREY,
+        "lightpink" => css::LIGHT_PINK,
+        "lightsalmon" => css::LIGHT_SALMON,
+        "lightseagreen" => css::LIGHT_SEA_GREEN,
+        "lightskyblue" => css::LIGHT_SKY_BLUE,
+        "lightslategray" => css::LIGHT_SLATE_GRAY,
+        "lightslategrey" => css::LIGHT_SLATE_GREY,
+        "lightsteelblue" => css::LIGHT_STEEL_BLUE,
+        "lightyellow" => css::LIGHT_YELLOW,
+        "lime" => css::LIME,
+        "limegreen" => css::LIMEGREEN,
+        "linen" => css::LINEN,
+        "magenta" => css::MAGENTA,
+        "maroon" => css::MAROON,
+        "mediumaquamarine" => css::MEDIUM_AQUAMARINE,
+        "mediumblue" => css::MEDIUM_BLUE,
+        "mediumorchid" => css::MEDIUM_ORCHID,
+        "mediumpurple" => css::MEDIUM_PURPLE,
+        "mediumseagreen" => css::MEDIUM_SEA_GREEN,
+        "mediumslateblue" => css::MEDIUM_SLATE_BLUE,
+        "mediumspringgreen" => css::MEDIUM_SPRING_GREEN,
+        "mediumturquoise" => css::MEDIUM_TURQUOISE,
+        "mediumvioletred" => css::MEDIUM_VIOLET_RED,
+        "midnightblue" => css::MIDNIGHT_BLUE,
+        "mintcream" => css::MINT_CREAM,
+        "mistyrose" => css::MISTY_ROSE,
+        "moccasin" => css::MOCCASIN,
+        "navajowhite" => css::NAVAJO_WHITE,
+        "navy" => css::NAVY,
+        "oldlace" => css::OLD_LACE,
+        "olive" => css::OLIVE,
+        "olivedrab" => css::OLIVE_DRAB,
+        "orange" => css::ORANGE,
+        "orangered" => css::ORANGE_RED,
+        "orchid" => css::ORCHID,
+        "palegoldenrod" => css::PALE_GOLDENROD,
+        "palegreen" => css::PALE_GREEN,
+        "paleturquoise" => css::PALE_TURQUOISE,
+        "palevioletred" => css::PALE_VIOLETRED,
+        "papayawhip" => css::PAPAYA_WHIP,
+        "peachpuff" => css::PEACHPUFF,
+        "peru" => css::PERU,
+        "pink" => css::PINK,
+        "plum" => css::PLUM,
+        "powderblue" => css::POWDER_BLUE,
+        "purple" => css::PURPLE,
+        "rebeccapurple" => css::REBECCA_PURPLE,
+        "red" => css::RED,
+        "rosybrown" => css::ROSY_BROWN,
+        "royalblue" => css::ROYAL_BLUE,
+        "saddlebrown" => css::SADDLE_BROWN,
+        "salmon" => css::SALMON,
+        "sandybrown" => css::SANDY_BROWN,
+        "seagreen" => css::SEA_GREEN,
+        "seashell" => css::SEASHELL,
+        "sienna" => css::SIENNA,
+        "silver" => css::SILVER,
+        "skyblue" => css::SKY_BLUE,
+        "slateblue" => css::SLATE_BLUE,
+        "slategray" => css::SLATE_GRAY,
+        "slategrey" => css::SLATE_GREY,
+        "snow" => css::SNOW,
+        "springgreen" => css::SPRING_GREEN,
+        "steelblue" => css::STEEL_BLUE,
+        "tan" => css::TAN,
+        "teal" => css::TEAL,
+        "thistle" => css::THISTLE,
+        "tomato" => css::TOMATO,
+        "turquoise" => css::TURQUOISE,
+        "violet" => css::VIOLET,
+        "wheat" => css::WHEAT,
+        "white" => css::WHITE,
+        "whitesmoke" => css::WHITE_SMOKE,
+        "yellow" => css::YELLOW,
+        "yellowgreen" => css::YELLOW_GREEN,
+        "transparent" => Srgba::NONE,
+        _ => return None,
+    };
+
+    Some(color.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_colors() {
+        assert_eq!(parse_color_value("#ff0000"), Some(Color::srgb(1., 0., 0.)));
+        assert_eq!(
+            parse_color_value("#f008"),
+            Some(Color::srgba(1., 0., 0., 0x88 as f32 / 255.))
+        );
+        assert_eq!(parse_color_value("#xyz"), None);
+    }
+
+    #[test]
+    fn parse_color_functions() {
+        assert_eq!(
+            parse_color_value("rgb(255, 0, 0)"),
+            Some(Color::srgb(1., 0., 0.))
+        );
+        assert_eq!(
+            parse_color_value("rgba(0 255 0 / 50%)"),
+            Some(Color::srgba(0., 1., 0., 0.5))
+        );
+        assert_eq!(
+            parse_color_value("hsl(120deg,100%,50%)"),
+            Some(Color::hsl(120., 1., 0.5))
+        );
+        assert_eq!(parse_color_value("rgb(1, 2)"), None);
+        assert_eq!(parse_color_value("primary"), None);
+    }
+
+    #[test]
+    fn css_color_names() {
+        assert_eq!(
+            css_color("CornflowerBlue"),
+            Some(css::CORNFLOWER_BLUE.into())
+        );
+        assert_eq!(css_color("dark-red"), Some(css::DARK_RED.into()));
+        assert_eq!(css_color("primary"), None);
+    }
+}
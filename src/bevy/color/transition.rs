@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+
+/// A gradual change of named colors, instead of switching to the new color instantly.
+///
+/// Used via [`ColorMap::insert_with_transition`](crate::ColorMap::insert_with_transition)
+/// and [`ColorMap::set_active_palette_with_transition`](crate::ColorMap::set_active_palette_with_transition).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorTransition {
+    /// The duration of the transition, in seconds.
+    pub duration: f32,
+    /// The easing applied to the progress of the transition.
+    pub easing: EaseFunction,
+}
+
+impl ColorTransition {
+    /// Transition linearly over the given duration in seconds.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            easing: EaseFunction::Linear,
+        }
+    }
+
+    /// Use the given easing for the transition.
+    pub fn with_easing(mut self, easing: EaseFunction) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+/// A running transition of the color of a text span.
+#[derive(Debug, Clone, Component)]
+pub(crate) struct TextColorTransition {
+    /// The color at the start of the transition.
+    from: Color,
+    /// The color at the end of the transition.
+    to: Color,
+    /// The time in seconds since the transition started.
+    elapsed: f32,
+    transition: ColorTransition,
+}
+
+impl TextColorTransition {
+    pub(crate) fn new(from: Color, to: Color, transition: ColorTransition) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.,
+            transition,
+        }
+    }
+
+    /// The progress of the transition between `0` and `1`, with the easing applied.
+    fn progress(&self) -> f32 {
+        let t = self.elapsed / self.transition.duration;
+        EasingCurve::new(0., 1., self.transition.easing).sample_clamped(t)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.transition.duration
+    }
+}
+
+/// Move the colors of the text spans towards their new color.
+pub(super) fn animate_color_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut transition_query: Query<(Entity, &mut TextColorTransition, &mut TextColor)>,
+) {
+    for (entity, mut transition, mut text_color) in transition_query.iter_mut() {
+        transition.elapsed += time.delta_secs();
+
+        if transition.is_finished() {
+            text_color.0 = transition.to;
+            commands.entity(entity).remove::<TextColorTransition>();
+        } else {
+            text_color.0 = transition.from.mix(&transition.to, transition.progress());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::{
+        super::{send_color_changes, update_colors, BbCodeColored, ColorMap, ColorMapChanged},
+        *,
+    };
+
+    const RED: Color = Color::srgb(1., 0., 0.);
+    const BLUE: Color = Color::srgb(0., 0., 1.);
+
+    #[test]
+    fn named_colors_transition_to_new_value() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                250,
+            )))
+            .init_resource::<ColorMap>()
+            .add_event::<ColorMapChanged>()
+            .add_systems(
+                Update,
+                (send_color_changes, update_colors, animate_color_transitions).chain(),
+            );
+
+        let span = app
+            .world_mut()
+            .spawn((
+                BbCodeColored {
+                    name: "accent".into(),
+                    fallbacks: Vec::new(),
+                    modifiers: Vec::new(),
+                },
+                TextColor(RED),
+            ))
+            .id();
+        app.update();
+
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert_with_transition("accent", BLUE, ColorTransition::new(1.));
+        app.update();
+
+        let color = app.world().get::<TextColor>(span).unwrap().0;
+        assert_ne!(color, RED);
+        assert_ne!(color, BLUE);
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        assert_eq!(app.world().get::<TextColor>(span).unwrap().0, BLUE);
+        assert!(app.world().get::<TextColorTransition>(span).is_none());
+    }
+}
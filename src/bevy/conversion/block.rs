@@ -0,0 +1,441 @@
+use bevy::prelude::*;
+
+use crate::bbcode::BbcodeTag;
+
+use super::{
+    context::{BbcodeContext, TextGradient},
+    spans::{TagCounter, TagOccurrence},
+};
+
+/// A converted span of text, which has not been spawned yet.
+#[derive(Debug, Clone)]
+pub(super) enum Span {
+    /// Text with the given style.
+    Text {
+        text: String,
+        context: Box<BbcodeContext>,
+    },
+    /// A pause of the given duration in seconds when revealing the text.
+    Pause(f32),
+}
+
+/// A block of text with its own alignment, e.g. a paragraph.
+#[derive(Debug, Default)]
+pub(super) struct TextBlock {
+    /// The alignment of the text, if it's set via a tag.
+    pub(super) justify: Option<JustifyText>,
+    /// The spans of the text.
+    pub(super) spans: Vec<Span>,
+    /// Whether the block follows a block tag, so a leading line break can be removed.
+    follows_block: bool,
+}
+
+impl TextBlock {
+    /// A block with the given spans and the default alignment.
+    pub(super) fn new(spans: Vec<Span>) -> Self {
+        Self { spans, ..default() }
+    }
+
+    /// Whether the block doesn't contain any visible text.
+    fn is_blank(&self) -> bool {
+        self.spans.iter().all(|span| match span {
+            Span::Text { text, .. } => text.trim().is_empty(),
+            Span::Pause(_) => true,
+        })
+    }
+
+    /// Whether any span of the block has a background color.
+    pub(super) fn has_background(&self) -> bool {
+        self.spans.iter().any(|span| match span {
+            Span::Text { context, .. } => context.background.is_some(),
+            Span::Pause(_) => false,
+        })
+    }
+
+    /// Whether the block needs its own node, because decorations are drawn next to the text.
+    pub(super) fn is_decorated(&self) -> bool {
+        self.has_background() || !self.layers().is_empty()
+    }
+
+    /// The copies of the text needed to draw the outlines and shadows, in the order they are drawn.
+    pub(super) fn layers(&self) -> Vec<Layer> {
+        let mut shadows = Vec::new();
+        let mut outlines = Vec::new();
+
+        for span in &self.spans {
+            let Span::Text { context, .. } = span else {
+                continue;
+            };
+
+            if let Some(shadow) = &context.shadow {
+                let layer = Layer::Shadow(shadow.offset);
+                if !shadows.contains(&layer) {
+                    shadows.push(layer);
+                }
+            }
+
+            if let Some(outline) = &context.outline {
+                let layer = Layer::Outline(outline.width);
+                if outline.width > 0. && !outlines.contains(&layer) {
+                    outlines.push(layer);
+                }
+            }
+        }
+
+        // The shadows are drawn below the outlines
+        shadows.extend(outlines);
+        shadows
+    }
+
+    /// Remove the line breaks which only separate the block from the surrounding block tags.
+    fn trim_line_breaks(&mut self, trim_end: bool) {
+        let mut texts: Vec<&mut String> = self
+            .spans
+            .iter_mut()
+            .filter_map(|span| match span {
+                Span::Text { text, .. } => Some(text),
+                Span::Pause(_) => None,
+            })
+            .collect();
+
+        if self.follows_block {
+            if let Some(text) = texts.first_mut() {
+                if let Some(trimmed) = text.strip_prefix("\r\n").or(text.strip_prefix('\n')) {
+                    **text = trimmed.to_owned();
+                }
+            }
+        }
+
+        if trim_end {
+            if let Some(text) = texts.last_mut() {
+                if let Some(trimmed) = text.strip_suffix("\r\n").or(text.strip_suffix('\n')) {
+                    **text = trimmed.to_owned();
+                }
+            }
+        }
+    }
+}
+
+/// A copy of a text block drawn behind it, to draw outlines and shadows.
+///
+/// Spans with a different outline width or shadow offset are drawn in their own layers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Layer {
+    /// A shadow with the given offset.
+    Shadow(Vec2),
+    /// An outline with the given width.
+    Outline(f32),
+}
+
+impl Layer {
+    /// The offsets of the copies of the text, in logical pixels.
+    pub(super) fn offsets(&self) -> Vec<Vec2> {
+        match *self {
+            Self::Shadow(offset) => vec![offset],
+            Self::Outline(width) => {
+                let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+
+                [
+                    Vec2::X,
+                    Vec2::NEG_X,
+                    Vec2::Y,
+                    Vec2::NEG_Y,
+                    Vec2::new(diagonal, diagonal),
+                    Vec2::new(diagonal, -diagonal),
+                    Vec2::new(-diagonal, diagonal),
+                    Vec2::new(-diagonal, -diagonal),
+                ]
+                .into_iter()
+                .map(|direction| direction * width)
+                .collect()
+            }
+        }
+    }
+
+    /// The span in the copy of the text.
+    ///
+    /// Only the spans belonging to the layer are visible, in the color of the outline or shadow.
+    pub(super) fn span(&self, span: &Span) -> Span {
+        let Span::Text { text, context } = span else {
+            return span.clone();
+        };
+
+        let color = match *self {
+            Self::Shadow(offset) => context
+                .shadow
+                .as_ref()
+                .filter(|shadow| shadow.offset == offset)
+                .map(|shadow| shadow.color.clone()),
+            Self::Outline(width) => context
+                .outline
+                .as_ref()
+                .filter(|outline| outline.width == width)
+                .map(|outline| outline.color.clone()),
+        };
+
+        let color = color.unwrap_or(Color::NONE.into());
+
+        Span::Text {
+            text: text.clone(),
+            context: Box::new(BbcodeContext {
+                // Keep the gradient, so the copies are split into the same characters
+                gradient: context.gradient.as_ref().map(|gradient| TextGradient {
+                    from: color.clone(),
+                    to: color.clone(),
+                    ..gradient.clone()
+                }),
+                color,
+                // The layer is invisible while its color is not registered
+                inherited_colors: vec![Color::NONE.into()],
+                color_modifiers: Vec::new(),
+                markers: Vec::new(),
+                tags: Vec::new(),
+                tooltip: None,
+                background: None,
+                outline: None,
+                shadow: None,
+                // Keep the effects, so the copies are split the same way and move in sync with the text.
+                // Color effects are only animated on the original text.
+                ..(**context).clone()
+            }),
+        }
+    }
+}
+
+/// A block of the layout, displayed below each other.
+#[derive(Debug)]
+pub(super) enum Block {
+    /// A block of text, e.g. a paragraph or heading.
+    Text(TextBlock),
+    /// A horizontal rule.
+    Rule,
+    /// A quote containing other blocks.
+    Quote(Vec<Block>),
+    /// A code block containing other blocks.
+    Code(Vec<Block>),
+    /// A list, where each item contains other blocks.
+    List {
+        marker: ListMarker,
+        /// The style of the bullets or numbers.
+        context: Box<BbcodeContext>,
+        items: Vec<Vec<Block>>,
+    },
+    /// An item of a list, only valid while the list is being built.
+    ListItem(Vec<Block>),
+    /// A table, laid out in a grid.
+    Table {
+        /// The width of the borders, in logical pixels.
+        border: f32,
+        /// The padding of the cells, in logical pixels.
+        padding: f32,
+        rows: Vec<Vec<TableCell>>,
+    },
+    /// A row of a table, only valid while the table is being built.
+    TableRow(Vec<Block>),
+    /// A cell of a table, only valid while the table is being built.
+    TableCell(TableCell),
+}
+
+/// A cell of a table containing other blocks.
+#[derive(Debug)]
+pub(super) struct TableCell {
+    /// The number of columns the cell spans.
+    pub(super) colspan: u16,
+    pub(super) blocks: Vec<Block>,
+}
+
+/// The kind of marker in front of the items of a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ListMarker {
+    /// A bullet, for `[list]`.
+    Bullet,
+    /// Numbers, for `[list=1]`.
+    Decimal,
+    /// Lowercase letters, for `[list=a]`.
+    LowerAlpha,
+    /// Uppercase letters, for `[list=A]`.
+    UpperAlpha,
+    /// Lowercase roman numerals, for `[list=i]`.
+    LowerRoman,
+    /// Uppercase roman numerals, for `[list=I]`.
+    UpperRoman,
+}
+
+impl ListMarker {
+    /// Get the marker for the parameter of the list tag.
+    pub(super) fn from_param(param: Option<&str>) -> Option<Self> {
+        match param.map(str::trim) {
+            None | Some("") => Some(Self::Bullet),
+            Some("1") => Some(Self::Decimal),
+            Some("a") => Some(Self::LowerAlpha),
+            Some("A") => Some(Self::UpperAlpha),
+            Some("i") => Some(Self::LowerRoman),
+            Some("I") => Some(Self::UpperRoman),
+            Some(_) => None,
+        }
+    }
+
+    /// The text of the marker of the item with the given index, starting at 0.
+    pub(super) fn format(&self, index: usize, bullet: &str, suffix: &str) -> String {
+        let number = index + 1;
+
+        match self {
+            Self::Bullet => bullet.to_string(),
+            Self::Decimal => format!("{number}{suffix}"),
+            Self::LowerAlpha => format!("{}{suffix}", alphabetic(number)),
+            Self::UpperAlpha => format!("{}{suffix}", alphabetic(number).to_uppercase()),
+            Self::LowerRoman => format!("{}{suffix}", roman(number)),
+            Self::UpperRoman => format!("{}{suffix}", roman(number).to_uppercase()),
+        }
+    }
+}
+
+/// Format the number as letters, i.e. `a` to `z`, then `aa`, `ab`, ...
+fn alphabetic(mut number: usize) -> String {
+    let mut letters = Vec::new();
+
+    while number > 0 {
+        number -= 1;
+        letters.push((b'a' + (number % 26) as u8) as char);
+        number /= 26;
+    }
+
+    letters.iter().rev().collect()
+}
+
+/// Format the number as lowercase roman numerals.
+fn roman(mut number: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+
+    let mut result = String::new();
+
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            result.push_str(numeral);
+            number -= value;
+        }
+    }
+
+    result
+}
+
+/// Collects the converted spans into blocks.
+#[derive(Debug)]
+pub(super) struct BlockBuilder {
+    /// The blocks of the currently open containers, starting with the root.
+    containers: Vec<Vec<Block>>,
+    /// The text block that spans are currently added to.
+    current: TextBlock,
+    /// The numbering of the named tags.
+    tags: TagCounter,
+}
+
+impl Default for BlockBuilder {
+    fn default() -> Self {
+        Self {
+            containers: vec![Vec::new()],
+            current: TextBlock::default(),
+            tags: TagCounter::default(),
+        }
+    }
+}
+
+impl BlockBuilder {
+    /// Add a span to the current text block.
+    pub(super) fn push(&mut self, span: Span) {
+        self.current.spans.push(span);
+    }
+
+    /// Finish the current text block and start a new one with the given alignment.
+    pub(super) fn start_block(&mut self, justify: Option<JustifyText>) {
+        self.finish_text_block(justify, true);
+    }
+
+    /// Add a block after the current text block.
+    pub(super) fn push_block(&mut self, block: Block) {
+        self.start_block(self.current.justify);
+        self.push_to_container(block);
+    }
+
+    /// Start a container block, all following blocks are added to it until it's closed.
+    pub(super) fn open_container(&mut self) {
+        self.start_block(self.current.justify);
+        self.containers.push(Vec::new());
+    }
+
+    /// Close the innermost container block and create it from the blocks it contains.
+    pub(super) fn close_container(&mut self, create: impl FnOnce(Vec<Block>) -> Block) {
+        self.start_block(self.current.justify);
+
+        // The root can't be closed
+        if self.containers.len() > 1 {
+            let blocks = self.containers.pop().unwrap_or_default();
+            self.push_to_container(create(blocks));
+        }
+    }
+
+    /// Number the named tags of the tag, in the order of the text.
+    pub(super) fn count_tags(&mut self, tag: &BbcodeTag) -> Vec<TagOccurrence> {
+        self.tags.count(tag)
+    }
+
+    /// A separate builder, e.g. to inspect the content of a tag before adding it.
+    ///
+    /// The tags are numbered as if the content was added to this builder.
+    pub(super) fn detached(&self) -> Self {
+        Self {
+            tags: self.tags.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Continue with the numbering of the tags, e.g. of a detached builder.
+    pub(super) fn set_tags(&mut self, tags: TagCounter) {
+        self.tags = tags;
+    }
+
+    /// Finish the current text block and return all blocks and the numbering of the tags.
+    pub(super) fn finish(mut self) -> (Vec<Block>, TagCounter) {
+        self.finish_text_block(None, false);
+        (self.containers.swap_remove(0), self.tags)
+    }
+
+    fn finish_text_block(&mut self, justify: Option<JustifyText>, is_block_start: bool) {
+        let mut block = std::mem::replace(
+            &mut self.current,
+            TextBlock {
+                justify,
+                spans: Vec::new(),
+                follows_block: is_block_start,
+            },
+        );
+
+        // Skip the whitespace between block tags
+        if block.is_blank() {
+            return;
+        }
+
+        block.trim_line_breaks(is_block_start);
+        self.push_to_container(Block::Text(block));
+    }
+
+    fn push_to_container(&mut self, block: Block) {
+        if let Some(container) = self.containers.last_mut() {
+            container.push(block);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use bevy::prelude::*;
+
+use crate::{
+    bbcode::BbcodeTag,
+    bevy::{
+        bbcode::BbcodeSettings,
+        color::{
+            apply_modifiers, is_color_value, parse_color_value, resolve_color_chain, BbCodeColor,
+            ColorModifier, ColorScope,
+        },
+        effect::TextEffects,
+        layer::{TextOutline, TextShadow},
+    },
+};
+
+use super::spans::TagOccurrence;
+
+/// The style and other properties of the text inside of a tag.
+#[derive(Debug, Clone)]
+pub(super) struct BbcodeContext {
+    /// The name of the font family to use for the text.
+    pub(super) font_family: String,
+    /// The font size of the text.
+    pub(super) font_size: f32,
+    /// Whether the text should be written **bold**.
+    pub(super) is_bold: bool,
+    /// Whether the text should be written *italic*.
+    pub(super) is_italic: bool,
+    /// The color of the text.
+    pub(super) color: BbCodeColor,
+    /// The colors to fall back to while the color name is not registered, innermost first.
+    ///
+    /// Ends with a static color, so the color can always be resolved.
+    pub(super) inherited_colors: Vec<BbCodeColor>,
+    /// The modifiers applied to the resolved color, e.g. to make it transparent.
+    ///
+    /// The modifiers of a color tag come first, followed by the ones of the surrounding tags.
+    pub(super) color_modifiers: Vec<ColorModifier>,
+    /// The color gradient across the characters of a `[gradient]` tag.
+    pub(super) gradient: Option<TextGradient>,
+    /// The background color behind the text.
+    pub(super) background: Option<BbCodeColor>,
+    /// The outline around the text.
+    pub(super) outline: Option<TextOutline>,
+    /// The drop shadow behind the text.
+    pub(super) shadow: Option<TextShadow>,
+    /// Whether the text should use a monospace font, e.g. for code.
+    pub(super) is_monospace: bool,
+
+    /// Marker components to apply to the spawned `Text`s.
+    pub(super) markers: Vec<String>,
+    /// The named tags containing the text, to record their spans.
+    pub(super) tags: Vec<TagOccurrence>,
+    /// The bbcode content of the tooltip to show when hovering the text.
+    pub(super) tooltip: Option<String>,
+    /// The multiplier for the speed in which the text is revealed.
+    pub(super) reveal_speed: f32,
+    /// The animated effects of the text.
+    pub(super) effects: TextEffects,
+    /// The number of nested translations, to detect translations including themselves.
+    pub(super) translation_depth: u8,
+    /// The alignment of the text block.
+    pub(super) justify: Option<JustifyText>,
+}
+
+impl BbcodeContext {
+    /// Whether the tag starts a new block of text, e.g. a paragraph.
+    pub(super) fn is_block_tag(tag: &BbcodeTag) -> bool {
+        matches!(
+            tag.name(),
+            "left" | "center" | "right" | "justify" | "p" | "h1" | "h2" | "h3"
+        )
+    }
+
+    /// The inherited colors of a nested tag changing the color.
+    pub(super) fn nested_inherited_colors(&self) -> Vec<BbCodeColor> {
+        match self.color {
+            // Static colors can always be resolved, so the outer colors are never needed
+            BbCodeColor::Static(_) => vec![self.color.clone()],
+            BbCodeColor::Named(_) => std::iter::once(self.color.clone())
+                .chain(self.inherited_colors.iter().cloned())
+                .collect(),
+        }
+    }
+
+    /// The color of the text, falling back to the inherited colors for unregistered names.
+    pub(super) fn resolve_color(&self, scope: ColorScope) -> Color {
+        let color = resolve_color_chain(
+            std::iter::once(&self.color).chain(&self.inherited_colors),
+            scope,
+        )
+        .unwrap_or(Color::WHITE);
+
+        apply_modifiers(color, &self.color_modifiers)
+    }
+
+    /// Change the style according to the tag.
+    pub(super) fn apply_tag(&self, tag: &BbcodeTag, settings: &BbcodeSettings) -> Self {
+        match tag.name() {
+            "b" => Self {
+                is_bold: true,
+                ..self.clone()
+            },
+            "i" => Self {
+                is_italic: true,
+                ..self.clone()
+            },
+            "c" | "color" => {
+                if let Some(color) = tag.simple_param() {
+                    match parse_color(color) {
+                        Some(color) => Self {
+                            color,
+                            inherited_colors: self.nested_inherited_colors(),
+                            color_modifiers: [
+                                parse_color_modifiers(tag),
+                                self.color_modifiers.clone(),
+                            ]
+                            .concat(),
+                            ..self.clone()
+                        },
+                        None => self.clone(),
+                    }
+                } else {
+                    warn!("Missing bbcode color on [{}] tag", tag.name());
+                    self.clone()
+                }
+            }
+            "fade" => match tag.simple_param().as_deref().map(parse_factor) {
+                Some(Some(factor)) => {
+                    let mut color_modifiers = self.color_modifiers.clone();
+                    color_modifiers.push(ColorModifier::Fade(factor));
+
+                    Self {
+                        color_modifiers,
+                        ..self.clone()
+                    }
+                }
+                Some(None) => {
+                    warn!("Invalid fade factor on [{}] tag", tag.name());
+                    self.clone()
+                }
+                None => {
+                    warn!("Missing fade factor on [{}] tag", tag.name());
+                    self.clone()
+                }
+            },
+            "bg" | "highlight" => {
+                if let Some(color) = tag.simple_param() {
+                    Self {
+                        background: parse_color(color).or_else(|| self.background.clone()),
+                        ..self.clone()
+                    }
+                } else {
+                    warn!("Missing background color on [{}] tag", tag.name());
+                    self.clone()
+                }
+            }
+            "m" | "marker" => {
+                if let Some(marker) = tag.simple_param() {
+                    let mut markers = self.markers.clone();
+                    markers.push(marker.to_string());
+
+                    Self {
+                        markers,
+                        ..self.clone()
+                    }
+                } else {
+                    warn!("Missing marker name on [{}] tag", tag.name());
+                    self.clone()
+                }
+            }
+            "id" => {
+                // The spans of the tag are recorded in `BbcodeSpans`
+                if tag.simple_param().is_none() {
+                    warn!("Missing id on [{}] tag", tag.name());
+                }
+                self.clone()
+            }
+            "font" => {
+                if let Some(font_family) = tag.simple_param() {
+                    Self {
+                        font_family: font_family.to_string(),
+                        ..self.clone()
+                    }
+                } else {
+                    warn!("Missing font family name on [{}] tag", tag.name());
+                    self.clone()
+                }
+            }
+            "outline" => {
+                let default = TextOutline::default();
+
+                Self {
+                    outline: Some(TextOutline {
+                        color: tag
+                            .simple_param()
+                            .as_deref()
+                            .and_then(parse_color)
+                            .unwrap_or(default.color),
+                        width: parse_length(tag, "width", default.width),
+                    }),
+                    ..self.clone()
+                }
+            }
+            "shadow" => {
+                let default = TextShadow::default();
+
+                Self {
+                    shadow: Some(TextShadow {
+                        color: tag
+                            .simple_param()
+                            .as_deref()
+                            .and_then(parse_color)
+                            .unwrap_or(default.color),
+                        offset: Vec2::new(
+                            parse_offset(tag, "x", default.offset.x),
+                            parse_offset(tag, "y", default.offset.y),
+                        ),
+                    }),
+                    ..self.clone()
+                }
+            }
+            "code" | "tt" => Self {
+                font_family: settings.monospace_font_family.clone(),
+                is_monospace: true,
+                ..self.clone()
+            },
+            "tooltip" => {
+                if let Some(tooltip) = tag.simple_param() {
+                    Self {
+                        tooltip: Some(tooltip.to_string()),
+                        ..self.clone()
+                    }
+                } else {
+                    warn!("Missing tooltip content on [{}] tag", tag.name());
+                    self.clone()
+                }
+            }
+            "speed" => match tag
+                .simple_param()
+                .as_ref()
+                .map(|speed| speed.trim().parse::<f32>())
+            {
+                Some(Ok(speed)) if speed > 0. => Self {
+                    reveal_speed: self.reveal_speed * speed,
+                    ..self.clone()
+                },
+                Some(_) => {
+                    warn!("Invalid reveal speed on [{}] tag", tag.name());
+                    self.clone()
+                }
+                None => {
+                    warn!("Missing reveal speed on [{}] tag", tag.name());
+                    self.clone()
+                }
+            },
+            "h1" | "h2" | "h3" => {
+                let level = tag.name()[1..].parse().unwrap_or(1);
+
+                Self {
+                    font_size: settings.font_size * settings.theme.heading_scale(level),
+                    is_bold: true,
+                    ..self.clone()
+                }
+            }
+            "left" | "center" | "right" | "justify" => Self {
+                justify: Some(match tag.name() {
+                    "left" => JustifyText::Left,
+                    "center" => JustifyText::Center,
+                    "right" => JustifyText::Right,
+                    _ => JustifyText::Justified,
+                }),
+                ..self.clone()
+            },
+            _ => {
+                let mut effects = self.effects;
+
+                if effects.apply_tag(tag) {
+                    Self {
+                        effects,
+                        ..self.clone()
+                    }
+                } else {
+                    self.clone()
+                }
+            }
+        }
+    }
+}
+
+/// The colors of a `[gradient]` tag and the position of a span inside of it.
+#[derive(Debug, Clone)]
+pub(super) struct TextGradient {
+    /// The color at the start of the gradient.
+    pub(super) from: BbCodeColor,
+    /// The color at the end of the gradient.
+    pub(super) to: BbCodeColor,
+    /// The index of the first character of the span in the gradient.
+    pub(super) start: usize,
+    /// The number of characters in the gradient.
+    pub(super) len: usize,
+}
+
+impl TextGradient {
+    /// The position of the character with the given index in the span, between `0` and `1`.
+    pub(super) fn position(&self, index: usize) -> f32 {
+        if self.len <= 1 {
+            return 0.;
+        }
+
+        (self.start + index) as f32 / (self.len - 1) as f32
+    }
+}
+
+/// Parse a color parameter, either a color value like `#ff0000` or `rgb(255,0,0)`,
+/// or a name of a color in the [`ColorMap`](crate::ColorMap) or a CSS color.
+///
+/// Returns `None` for invalid color values.
+pub(super) fn parse_color(color: &str) -> Option<BbCodeColor> {
+    if let Some(color) = parse_color_value(color) {
+        return Some(color.into());
+    }
+
+    if is_color_value(color) {
+        warn!("Invalid color value {color}");
+        return None;
+    }
+
+    Some(color.trim().to_string().into())
+}
+
+/// Parse the `alpha`, `lighten` and `darken` parameters of a color tag, in the order they are written.
+fn parse_color_modifiers(tag: &BbcodeTag) -> Vec<ColorModifier> {
+    tag.complex_params()
+        .iter()
+        .filter_map(|(key, value)| {
+            let modifier: fn(f32) -> ColorModifier = match *key {
+                "alpha" => ColorModifier::Alpha,
+                "lighten" => ColorModifier::Lighten,
+                "darken" => ColorModifier::Darken,
+                _ => return None,
+            };
+
+            let factor = parse_factor(value);
+            if factor.is_none() {
+                warn!("Invalid {key} parameter on [{}] tag", tag.name());
+            }
+
+            factor.map(modifier)
+        })
+        .collect()
+}
+
+/// Parse a factor between `0` and `1`.
+fn parse_factor(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|factor| (0. ..=1.).contains(factor))
+}
+
+/// Parse the non-negative length parameter with the given key, falling back to the default value.
+pub(super) fn parse_length(tag: &BbcodeTag, key: &str, default: f32) -> f32 {
+    match tag.param(key).map(|value| value.trim().parse::<f32>()) {
+        Some(Ok(value)) if value >= 0. => value,
+        Some(_) => {
+            warn!("Invalid {key} parameter on [{}] tag", tag.name());
+            default
+        }
+        None => default,
+    }
+}
+
+/// Parse the offset parameter with the given key, falling back to the default value.
+fn parse_offset(tag: &BbcodeTag, key: &str, default: f32) -> f32 {
+    match tag.param(key).map(|value| value.trim().parse::<f32>()) {
+        Some(Ok(value)) => value,
+        Some(Err(_)) => {
+            warn!("Invalid {key} parameter on [{}] tag", tag.name());
+            default
+        }
+        None => default,
+    }
+}
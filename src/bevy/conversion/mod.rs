@@ -0,0 +1,1883 @@
+mod block;
+mod context;
+mod spans;
+
+use std::sync::Arc;
+
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    ui::RelativeCursorPosition,
+    utils::{HashMap, HashSet},
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::bbcode::{
+    parser::{parse_bbcode, LIST_ITEM_TAG},
+    BbcodeNode,
+};
+
+use super::{
+    bbcode::{Bbcode, BbcodeSettings},
+    color::{
+        BbCodeBackgroundColored, BbCodeColor, BbCodeColored, BbCodeGradientColored, ColorOverrides,
+        ColorScope,
+    },
+    effect::{AnimatedGlyphs, GlyphIndex, GlyphOffset, TextEffects},
+    font::FontRegistry,
+    highlight::{TextBackground, TextHighlights},
+    layer::LayerSpan,
+    localization::BbcodeTranslations,
+    reveal::{RevealPause, RevealSpeed},
+    tooltip::BbcodeTooltip,
+    ColorMap,
+};
+use block::{Block, BlockBuilder, ListMarker, Span, TableCell, TextBlock};
+use context::{parse_color, parse_length, BbcodeContext, TextGradient};
+use spans::TagCounter;
+
+pub use spans::BbcodeSpans;
+
+/// The maximum number of nested translations, to avoid infinite recursion.
+const MAX_TRANSLATION_DEPTH: u8 = 8;
+
+/// The resources needed to spawn the converted blocks.
+struct SpawnContext<'a> {
+    settings: &'a BbcodeSettings,
+    /// The text layout of the root entity.
+    text_layout: &'a TextLayout,
+    font_registry: &'a FontRegistry,
+    /// The named colors available to the text.
+    color_scope: ColorScope<'a>,
+    /// The spans spawned so far.
+    spawned: SpawnedSpans,
+    /// The color names which are neither registered nor CSS colors, to warn about each of them once.
+    unknown_colors: &'a mut HashSet<String>,
+}
+
+/// The spans spawned for a `Bbcode` entity, without the copies for outlines and shadows.
+#[derive(Debug, Default)]
+struct SpawnedSpans {
+    /// The number of spawned `TextSpan`s, without the empty spans marking pauses.
+    count: usize,
+    /// The spans inside of each `[m]` tag, by marker name.
+    markers: HashMap<String, Vec<Entity>>,
+    /// The spans of each named tag occurrence.
+    tags: BbcodeSpans,
+}
+
+impl SpawnedSpans {
+    /// Start recording the spans of the named tags.
+    fn new(tags: &TagCounter) -> Self {
+        Self {
+            count: 0,
+            markers: HashMap::default(),
+            tags: BbcodeSpans::new(tags),
+        }
+    }
+}
+
+/// Sent after a [`Bbcode`] entity has been converted into text spans.
+///
+/// Read it after [`BbcodeSet::Convert`](crate::BbcodeSet::Convert), when the spans have been spawned,
+/// e.g. to insert additional components on them.
+/// When the content fails to parse, it's sent without any spans, because the previous spans are removed.
+#[derive(Debug, Clone, Event)]
+pub struct BbcodeRebuilt {
+    /// The `Bbcode` entity.
+    pub entity: Entity,
+    /// The number of spawned `TextSpan`s with text, without the copies for outlines and shadows.
+    ///
+    /// The empty spans marking the position of a `[pause]` are not included.
+    pub span_count: usize,
+    /// The spans inside of each `[m]` tag, by marker name.
+    pub markers: HashMap<String, Vec<Entity>>,
+}
+
+/// The layout of a [`Bbcode`] entity as set by the user.
+///
+/// The conversion changes the layout to display the blocks, so it's stored to restore it on the next conversion.
+#[derive(Debug, Clone, Copy, Component)]
+pub(crate) struct BaseLayout {
+    justify: JustifyText,
+    flex_direction: FlexDirection,
+    row_gap: Val,
+    /// The layout set by the last conversion, to detect changes made by the user since then.
+    converted: (JustifyText, FlexDirection, Val),
+}
+
+impl BaseLayout {
+    fn new(justify: JustifyText, flex_direction: FlexDirection, row_gap: Val) -> Self {
+        Self {
+            justify,
+            flex_direction,
+            row_gap,
+            converted: (justify, flex_direction, row_gap),
+        }
+    }
+}
+
+/// The `Bbcode` entities to convert, with their current layout.
+type BbcodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        Ref<'static, Bbcode>,
+        Ref<'static, BbcodeSettings>,
+        &'static TextLayout,
+        &'static Node,
+        Option<&'static BaseLayout>,
+    ),
+>;
+
+pub fn convert_bbcode(
+    mut commands: Commands,
+    bbcode_query: BbcodeQuery,
+    font_registry: Res<FontRegistry>,
+    color_map: Res<ColorMap>,
+    color_overrides: ColorOverrides,
+    translations: Option<Res<BbcodeTranslations>>,
+    mut rebuilt_events: EventWriter<BbcodeRebuilt>,
+) {
+    let mut unknown_colors = HashSet::new();
+
+    // Rebuild all text when the locale changes
+    let translations_changed = translations
+        .as_ref()
+        .is_some_and(|translations| translations.is_changed());
+
+    for (entity, bbcode, settings, text_layout, node, base_layout) in bbcode_query.iter() {
+        if !bbcode.is_changed()
+            && !settings.is_changed()
+            && !font_registry.is_changed()
+            && !translations_changed
+        {
+            continue;
+        }
+
+        let mut entity_commands = commands.entity(entity);
+
+        // Clear out all descendants to start fresh
+        entity_commands
+            .despawn_descendants()
+            .remove::<BbcodeSpans>();
+
+        let nodes = match parse_bbcode(&bbcode.content) {
+            Ok(("", nodes)) => nodes,
+            _ => {
+                warn!("Failed to parse bbcode\n{}", bbcode.content);
+
+                // The previous spans have been removed
+                rebuilt_events.send(BbcodeRebuilt {
+                    entity,
+                    span_count: 0,
+                    markers: HashMap::default(),
+                });
+                continue;
+            }
+        };
+
+        let mut blocks = BlockBuilder::default();
+
+        construct_recursively(
+            &mut blocks,
+            BbcodeContext {
+                font_family: settings.font_family.clone(),
+                font_size: settings.font_size,
+                is_bold: false,
+                is_italic: false,
+                color: settings.color.clone(),
+                inherited_colors: vec![Color::WHITE.into()],
+                color_modifiers: Vec::new(),
+                gradient: None,
+                background: None,
+                outline: settings.outline.clone(),
+                shadow: settings.shadow.clone(),
+                is_monospace: false,
+                markers: Vec::new(),
+                tags: Vec::new(),
+                tooltip: None,
+                reveal_speed: 1.,
+                effects: TextEffects::default(),
+                translation_depth: 0,
+                justify: None,
+            },
+            &settings,
+            &nodes,
+            translations.as_deref(),
+        );
+
+        // Keep the layout of the user, unless it has been changed since the last conversion
+        let current_layout = (text_layout.justify, node.flex_direction, node.row_gap);
+        let mut base_layout = match base_layout {
+            Some(base_layout) if base_layout.converted == current_layout => *base_layout,
+            _ => BaseLayout::new(current_layout.0, current_layout.1, current_layout.2),
+        };
+        let base_text_layout = TextLayout {
+            justify: base_layout.justify,
+            ..*text_layout
+        };
+        let mut layout = (
+            base_layout.justify,
+            base_layout.flex_direction,
+            base_layout.row_gap,
+        );
+
+        let (mut blocks, tags) = blocks.finish();
+        let mut spawn_context = SpawnContext {
+            settings: &settings,
+            text_layout: &base_text_layout,
+            font_registry: font_registry.as_ref(),
+            color_scope: ColorScope::new(&color_map, color_overrides.get(entity)),
+            spawned: SpawnedSpans::new(&tags),
+            unknown_colors: &mut unknown_colors,
+        };
+
+        // Decorations are drawn as separate nodes next to the text, so the text needs its own node
+        if let [Block::Text(block)] = blocks.as_mut_slice() {
+            if !block.is_decorated() {
+                // A single text block can be displayed directly in the root text
+                if let Some(justify) = block.justify {
+                    layout.0 = justify;
+                }
+
+                let spans = std::mem::take(&mut block.spans);
+                collect_unknown_colors(&spans, &mut spawn_context);
+                let pause_count = pause_count(&spans);
+                let span_entities = spawn_spans(&mut entity_commands, spans, &mut spawn_context);
+                spawn_context.spawned.count += span_entities.len() - pause_count;
+                blocks.clear();
+            }
+        }
+
+        // Otherwise, display the blocks below each other
+        if !blocks.is_empty() {
+            layout.1 = FlexDirection::Column;
+            layout.2 = Val::Px(settings.theme.block_spacing);
+
+            spawn_blocks(&mut commands, entity, blocks, 0, &mut spawn_context);
+        }
+
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(spawn_context.spawned.tags);
+
+        if layout != current_layout {
+            let (justify, flex_direction, row_gap) = layout;
+            entity_commands.insert(TextLayout {
+                justify,
+                ..*text_layout
+            });
+            entity_commands.entry::<Node>().and_modify(move |mut node| {
+                node.flex_direction = flex_direction;
+                node.row_gap = row_gap;
+            });
+        }
+        base_layout.converted = layout;
+        entity_commands.insert(base_layout);
+
+        // The spans are spawned by the time the event is read after the conversion
+        rebuilt_events.send(BbcodeRebuilt {
+            entity,
+            span_count: spawn_context.spawned.count,
+            markers: spawn_context.spawned.markers,
+        });
+    }
+
+    let mut unknown_colors: Vec<_> = unknown_colors.into_iter().collect();
+    unknown_colors.sort();
+
+    for name in unknown_colors {
+        warn!(
+            "Unknown color {name}, register it in the ColorMap to use it instead of the fallback"
+        );
+    }
+}
+
+fn construct_recursively(
+    blocks: &mut BlockBuilder,
+    context: BbcodeContext,
+    settings: &BbcodeSettings,
+    nodes: &Vec<Arc<BbcodeNode>>,
+    translations: Option<&BbcodeTranslations>,
+) {
+    for node in nodes {
+        // Number the named tags in the order of the text, so their spans can be looked up
+        let tags = match **node {
+            BbcodeNode::Tag(ref tag) => blocks.count_tags(tag),
+            BbcodeNode::Text(_) => Vec::new(),
+        };
+        let tagged_context;
+        let context = if tags.is_empty() {
+            &context
+        } else {
+            tagged_context = BbcodeContext {
+                tags: [context.tags.as_slice(), &tags].concat(),
+                ..context.clone()
+            };
+            &tagged_context
+        };
+
+        match **node {
+            BbcodeNode::Text(ref text) => blocks.push(Span::Text {
+                text: text.to_string(),
+                context: Box::new(context.clone()),
+            }),
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "pause" => {
+                match tag
+                    .simple_param()
+                    .as_ref()
+                    .map(|pause| pause.trim().parse::<f32>())
+                {
+                    Some(Ok(pause)) => blocks.push(Span::Pause(pause)),
+                    Some(Err(_)) => warn!("Invalid pause duration on [{}] tag", tag.name()),
+                    None => warn!("Missing pause duration on [{}] tag", tag.name()),
+                }
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "t" => {
+                let Some(key) = tag.simple_param() else {
+                    warn!("Missing translation key on [{}] tag", tag.name());
+                    continue;
+                };
+
+                let args = tag
+                    .complex_params()
+                    .iter()
+                    .map(|(key, value)| (*key, value.as_ref()))
+                    .collect();
+
+                let translation = if context.translation_depth >= MAX_TRANSLATION_DEPTH {
+                    warn!("Translation {key} exceeds the maximum of {MAX_TRANSLATION_DEPTH} nested translations, does it include itself?");
+                    None
+                } else {
+                    let translation =
+                        translations.and_then(|translations| translations.translate(key, &args));
+                    if translation.is_none() {
+                        warn!("Missing translation for key {key}");
+                    }
+                    translation
+                };
+
+                let Some(translation) = translation else {
+                    // Show the key instead to make the missing translation visible
+                    blocks.push(Span::Text {
+                        text: key.to_string(),
+                        context: Box::new(context.clone()),
+                    });
+                    continue;
+                };
+
+                let nodes = match parse_bbcode(&translation) {
+                    Ok(("", nodes)) => nodes,
+                    _ => {
+                        warn!("Failed to parse bbcode of translation {key}\n{translation}");
+                        continue;
+                    }
+                };
+
+                construct_recursively(
+                    blocks,
+                    BbcodeContext {
+                        translation_depth: context.translation_depth + 1,
+                        ..context.clone()
+                    },
+                    settings,
+                    &nodes,
+                    translations,
+                );
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "hr" => blocks.push_block(Block::Rule),
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "gradient" => {
+                // Construct the content first, to know the number of characters
+                let mut gradient_blocks = blocks.detached();
+                construct_recursively(
+                    &mut gradient_blocks,
+                    context.clone(),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+
+                let (gradient_blocks, gradient_tags) = gradient_blocks.finish();
+                let Some(spans) = inline_spans(gradient_blocks) else {
+                    warn!("Only inline text is supported in [{}] tags", tag.name());
+                    construct_recursively(
+                        blocks,
+                        context.clone(),
+                        settings,
+                        tag.children(),
+                        translations,
+                    );
+                    continue;
+                };
+                blocks.set_tags(gradient_tags);
+
+                // Use the surrounding color for missing colors, e.g. `[gradient to=gold]`
+                let parse_param = |key| {
+                    tag.param(key)
+                        .and_then(parse_color)
+                        .unwrap_or_else(|| context.color.clone())
+                };
+                let (from, to) = (parse_param("from"), parse_param("to"));
+
+                let len = spans
+                    .iter()
+                    .map(|span| match span {
+                        Span::Text { text, .. } => text.graphemes(true).count(),
+                        Span::Pause(_) => 0,
+                    })
+                    .sum();
+                let mut start = 0;
+
+                for span in spans {
+                    let Span::Text { text, mut context } = span else {
+                        blocks.push(span);
+                        continue;
+                    };
+
+                    // Nested gradients take precedence
+                    if context.gradient.is_none() {
+                        context.gradient = Some(TextGradient {
+                            from: from.clone(),
+                            to: to.clone(),
+                            start,
+                            len,
+                        });
+                    }
+                    start += text.graphemes(true).count();
+
+                    blocks.push(Span::Text { text, context });
+                }
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "quote" => {
+                blocks.open_container();
+
+                // Show the author of the quote, e.g. `[quote=Alice]`
+                if let Some(author) = tag.simple_param() {
+                    blocks.push(Span::Text {
+                        text: format!("{author}:"),
+                        context: Box::new(BbcodeContext {
+                            is_italic: true,
+                            ..context.clone()
+                        }),
+                    });
+                    blocks.start_block(context.justify);
+                }
+
+                construct_recursively(
+                    blocks,
+                    context.clone(),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+                blocks.close_container(Block::Quote);
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "code" => {
+                blocks.open_container();
+                construct_recursively(
+                    blocks,
+                    context.apply_tag(tag, settings),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+                blocks.close_container(Block::Code);
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "list" => {
+                let marker =
+                    ListMarker::from_param(tag.simple_param().as_deref()).unwrap_or_else(|| {
+                        warn!("Invalid list style on [{}] tag", tag.name());
+                        ListMarker::Bullet
+                    });
+
+                blocks.open_container();
+                construct_recursively(
+                    blocks,
+                    context.clone(),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+                blocks.close_container(|items| Block::List {
+                    marker,
+                    context: Box::new(context.clone()),
+                    items: items
+                        .into_iter()
+                        .map(|item| match item {
+                            Block::ListItem(blocks) => blocks,
+                            // Content before the first item gets its own item
+                            block => vec![block],
+                        })
+                        .collect(),
+                });
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == LIST_ITEM_TAG => {
+                blocks.open_container();
+                construct_recursively(
+                    blocks,
+                    context.clone(),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+                blocks.close_container(Block::ListItem);
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "table" => {
+                let theme = &settings.theme;
+                let border = parse_length(tag, "border", theme.table_border_width);
+                let padding = parse_length(tag, "padding", theme.table_cell_padding);
+
+                blocks.open_container();
+                construct_recursively(
+                    blocks,
+                    context.clone(),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+                blocks.close_container(|rows| Block::Table {
+                    border,
+                    padding,
+                    rows: rows
+                        .into_iter()
+                        .filter_map(|row| match row {
+                            Block::TableRow(cells) => Some(table_cells(cells)),
+                            _ => {
+                                warn!("Ignoring content outside of [tr] in [table]");
+                                None
+                            }
+                        })
+                        .collect(),
+                });
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "tr" => {
+                blocks.open_container();
+                construct_recursively(
+                    blocks,
+                    context.clone(),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+                blocks.close_container(Block::TableRow);
+            }
+
+            BbcodeNode::Tag(ref tag) if tag.name() == "td" => {
+                let colspan = match tag.param("colspan").map(|colspan| colspan.trim().parse()) {
+                    Some(Ok(colspan)) if colspan > 0 => colspan,
+                    Some(_) => {
+                        warn!("Invalid colspan parameter on [{}] tag", tag.name());
+                        1
+                    }
+                    None => 1,
+                };
+
+                blocks.open_container();
+                construct_recursively(
+                    blocks,
+                    context.clone(),
+                    settings,
+                    tag.children(),
+                    translations,
+                );
+                blocks.close_container(|blocks| Block::TableCell(TableCell { colspan, blocks }));
+            }
+
+            BbcodeNode::Tag(ref tag) => {
+                let is_block = BbcodeContext::is_block_tag(tag);
+                let tag_context = context.apply_tag(tag, settings);
+
+                if is_block {
+                    blocks.start_block(tag_context.justify);
+                }
+
+                construct_recursively(blocks, tag_context, settings, tag.children(), translations);
+
+                if is_block {
+                    blocks.start_block(context.justify);
+                }
+            }
+        }
+    }
+}
+
+/// Collect the cells of a table row.
+fn table_cells(blocks: Vec<Block>) -> Vec<TableCell> {
+    blocks
+        .into_iter()
+        .filter_map(|block| match block {
+            Block::TableCell(cell) => Some(cell),
+            _ => {
+                warn!("Ignoring content outside of [td] in [tr]");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Spawn the blocks as children of the given parent entity.
+///
+/// The list depth is the number of lists the blocks are nested in.
+fn spawn_blocks(
+    commands: &mut Commands,
+    parent: Entity,
+    blocks: Vec<Block>,
+    list_depth: usize,
+    spawn_context: &mut SpawnContext,
+) {
+    let theme = &spawn_context.settings.theme;
+
+    for block in blocks {
+        match block {
+            Block::Text(block) => {
+                spawn_text_block(commands, parent, block, spawn_context);
+            }
+
+            Block::Rule => {
+                commands
+                    .spawn((
+                        Node {
+                            height: Val::Px(theme.rule_thickness),
+                            ..default()
+                        },
+                        BackgroundColor(theme.rule_color),
+                    ))
+                    .set_parent(parent);
+            }
+
+            Block::Quote(blocks) => {
+                let quote = commands
+                    .spawn((
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(theme.block_spacing),
+                            padding: UiRect::left(Val::Px(theme.quote_indent)),
+                            border: UiRect::left(Val::Px(theme.quote_border_width)),
+                            ..default()
+                        },
+                        BorderColor(theme.quote_border_color),
+                    ))
+                    .set_parent(parent)
+                    .id();
+
+                spawn_blocks(commands, quote, blocks, list_depth, spawn_context);
+            }
+
+            Block::Code(blocks) => {
+                let code = commands
+                    .spawn((
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            padding: UiRect::all(Val::Px(theme.code_padding)),
+                            ..default()
+                        },
+                        BackgroundColor(theme.code_background_color),
+                    ))
+                    .set_parent(parent)
+                    .id();
+
+                spawn_blocks(commands, code, blocks, list_depth, spawn_context);
+            }
+
+            Block::List {
+                marker,
+                context,
+                items,
+            } => {
+                let list = commands
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(theme.block_spacing),
+                        ..default()
+                    })
+                    .set_parent(parent)
+                    .id();
+
+                for (index, blocks) in items.into_iter().enumerate() {
+                    let item = commands
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            ..default()
+                        })
+                        .set_parent(list)
+                        .id();
+
+                    // The marker has a fixed width, so wrapped lines get a hanging indent
+                    let marker_text = marker.format(
+                        index,
+                        theme.list_bullet(list_depth),
+                        &theme.list_number_suffix,
+                    );
+                    let marker_entity = spawn_text_block(
+                        commands,
+                        item,
+                        TextBlock::new(vec![Span::Text {
+                            text: marker_text,
+                            context: context.clone(),
+                        }]),
+                        spawn_context,
+                    );
+                    commands.entity(marker_entity).insert(Node {
+                        width: Val::Px(theme.list_indent),
+                        flex_shrink: 0.,
+                        ..default()
+                    });
+
+                    let content = commands
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            flex_grow: 1.,
+                            flex_basis: Val::Px(0.),
+                            row_gap: Val::Px(theme.block_spacing),
+                            ..default()
+                        })
+                        .set_parent(item)
+                        .id();
+
+                    spawn_blocks(commands, content, blocks, list_depth + 1, spawn_context);
+                }
+            }
+
+            Block::Table {
+                border,
+                padding,
+                rows,
+            } => {
+                let columns = rows
+                    .iter()
+                    .map(|row| row.iter().map(|cell| cell.colspan).sum::<u16>())
+                    .max()
+                    .unwrap_or_default();
+
+                // The cells draw the right and bottom borders, so they don't overlap
+                let table = commands
+                    .spawn((
+                        Node {
+                            display: Display::Grid,
+                            grid_template_columns: RepeatedGridTrack::auto(columns.max(1)),
+                            border: UiRect {
+                                left: Val::Px(border),
+                                top: Val::Px(border),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        BorderColor(theme.table_border_color),
+                    ))
+                    .set_parent(parent)
+                    .id();
+
+                for (row_index, row) in rows.into_iter().enumerate() {
+                    let mut column = 1;
+
+                    for cell in row {
+                        let cell_entity = commands
+                            .spawn((
+                                Node {
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(theme.block_spacing),
+                                    padding: UiRect::all(Val::Px(padding)),
+                                    border: UiRect {
+                                        right: Val::Px(border),
+                                        bottom: Val::Px(border),
+                                        ..default()
+                                    },
+                                    grid_row: GridPlacement::start(row_index as i16 + 1),
+                                    grid_column: GridPlacement::start_span(
+                                        column as i16,
+                                        cell.colspan,
+                                    ),
+                                    ..default()
+                                },
+                                BorderColor(theme.table_border_color),
+                            ))
+                            .set_parent(table)
+                            .id();
+
+                        column += cell.colspan;
+                        spawn_blocks(
+                            commands,
+                            cell_entity,
+                            cell.blocks,
+                            list_depth,
+                            spawn_context,
+                        );
+                    }
+                }
+            }
+
+            // Items, rows and cells are unwrapped when their container is closed
+            Block::ListItem(blocks) | Block::TableRow(blocks) => {
+                spawn_blocks(commands, parent, blocks, list_depth, spawn_context);
+            }
+            Block::TableCell(cell) => {
+                spawn_blocks(commands, parent, cell.blocks, list_depth, spawn_context);
+            }
+        }
+    }
+}
+
+/// Spawn the text block in its own `Text` as child of the given parent entity.
+fn spawn_text_block(
+    commands: &mut Commands,
+    parent: Entity,
+    block: TextBlock,
+    spawn_context: &mut SpawnContext,
+) -> Entity {
+    let text_layout = spawn_context.text_layout;
+
+    // The decorations are drawn next to the text, so it's wrapped in its own node
+    let parent = if block.is_decorated() {
+        commands
+            .spawn(Node {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            })
+            .set_parent(parent)
+            .id()
+    } else {
+        parent
+    };
+
+    let text_bundle = (
+        Text::default(),
+        TextLayout {
+            justify: block.justify.unwrap_or(text_layout.justify),
+            ..*text_layout
+        },
+        TextFont {
+            font_size: spawn_context.settings.font_size,
+            ..default()
+        },
+    );
+
+    // The copies for outlines and shadows use the same colors
+    collect_unknown_colors(&block.spans, spawn_context);
+
+    // Spawn the copies for outlines and shadows first, so they are drawn below the text
+    let mut layer_spans = Vec::new();
+
+    for layer in block.layers() {
+        let spans: Vec<Span> = block.spans.iter().map(|span| layer.span(span)).collect();
+
+        for offset in layer.offsets() {
+            let mut layer_commands = commands.spawn((
+                text_bundle.clone(),
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(offset.x),
+                    top: Val::Px(offset.y),
+                    width: Val::Percent(100.),
+                    ..default()
+                },
+            ));
+            layer_commands.set_parent(parent);
+
+            layer_spans.push(spawn_spans(
+                &mut layer_commands,
+                spans.clone(),
+                spawn_context,
+            ));
+        }
+    }
+
+    let mut block_commands = commands.spawn(text_bundle);
+    block_commands.set_parent(parent);
+
+    let block_entity = block_commands.id();
+    let pause_count = pause_count(&block.spans);
+    let spans = spawn_spans(&mut block_commands, block.spans, spawn_context);
+    spawn_context.spawned.count += spans.len() - pause_count;
+
+    for layer_spans in layer_spans {
+        for (layer_span, &source) in layer_spans.into_iter().zip(&spans) {
+            commands.entity(layer_span).insert(LayerSpan { source });
+        }
+    }
+
+    block_entity
+}
+
+/// Spawn the spans as children of the text entity and return the spawned `TextSpan`s in order.
+fn spawn_spans(
+    entity_commands: &mut EntityCommands,
+    spans: Vec<Span>,
+    spawn_context: &mut SpawnContext,
+) -> Vec<Entity> {
+    let settings = spawn_context.settings;
+    let color_scope = spawn_context.color_scope;
+    let mut span_entities = Vec::new();
+    // The index of the first character of the span in the text, so effects continue across spans
+    let mut glyph_start = 0;
+
+    for span in spans {
+        let (text, context) = match span {
+            Span::Text { text, context } => (text, context),
+            Span::Pause(pause) => {
+                // Add an empty span to mark the position of the pause
+                entity_commands.with_children(|builder| {
+                    span_entities.push(
+                        builder
+                            .spawn((TextSpan::default(), RevealPause(pause)))
+                            .id(),
+                    );
+                });
+                continue;
+            }
+        };
+
+        let mut families = vec![fontdb::Family::Name(&context.font_family)];
+        if context.is_monospace {
+            families.push(fontdb::Family::Monospace);
+        }
+        families.push(fontdb::Family::Name(&settings.font_family));
+
+        let font_query = fontdb::Query {
+            families: &families,
+            weight: if context.is_bold {
+                fontdb::Weight::BOLD
+            } else {
+                fontdb::Weight::NORMAL
+            },
+            stretch: fontdb::Stretch::Normal,
+            style: if context.is_italic {
+                fontdb::Style::Italic
+            } else {
+                fontdb::Style::Normal
+            },
+        };
+        let font = spawn_context
+            .font_registry
+            .query_handle(&font_query)
+            .unwrap_or_default();
+
+        let text_font = TextFont {
+            font,
+            font_size: context.font_size,
+            ..default()
+        };
+        let split_glyphs = settings.split_glyphs && context.effects.is_any();
+        let first_entity_index = span_entities.len();
+        let span_glyph_start = glyph_start;
+        glyph_start += text.graphemes(true).count();
+
+        entity_commands.with_children(|builder| {
+            if split_glyphs || context.gradient.is_some() {
+                // Spawn each character separately to allow moving and coloring them independently
+                for (index, grapheme) in text.graphemes(true).enumerate() {
+                    let mut span_commands = spawn_span(
+                        builder,
+                        grapheme,
+                        text_font.clone(),
+                        &context,
+                        settings,
+                        color_scope,
+                    );
+
+                    if split_glyphs {
+                        span_commands
+                            .insert((GlyphOffset::default(), GlyphIndex(span_glyph_start + index)));
+                    }
+
+                    if let Some(gradient) = &context.gradient {
+                        let colored = BbCodeGradientColored {
+                            from: gradient.from.clone(),
+                            to: gradient.to.clone(),
+                            position: gradient.position(index),
+                            fallbacks: context.nested_inherited_colors(),
+                            modifiers: context.color_modifiers.clone(),
+                        };
+                        let color = colored.resolve(color_scope).unwrap_or(Color::WHITE);
+                        span_commands.insert((TextColor(color), colored));
+                    }
+
+                    span_entities.push(span_commands.id());
+                }
+            } else {
+                let span_entity =
+                    spawn_span(builder, &text, text_font, &context, settings, color_scope).id();
+                span_entities.push(span_entity);
+            }
+        });
+
+        for marker in &context.markers {
+            spawn_context
+                .spawned
+                .markers
+                .entry(marker.clone())
+                .or_default()
+                .extend(&span_entities[first_entity_index..]);
+        }
+
+        for tag in &context.tags {
+            spawn_context
+                .spawned
+                .tags
+                .record(tag, &span_entities[first_entity_index..]);
+        }
+
+        // The glyph positions are needed to draw the backgrounds
+        if context.background.is_some() {
+            entity_commands.insert(TextHighlights::default());
+        }
+
+        // The cursor position is needed to determine which span is hovered
+        if context.tooltip.is_some() {
+            entity_commands.insert(RelativeCursorPosition::default());
+        }
+
+        // The glyph positions need to be tracked to move them
+        if context.effects.moves_glyphs() || split_glyphs {
+            entity_commands.insert(AnimatedGlyphs::default());
+        }
+    }
+
+    span_entities
+}
+
+/// The number of pauses in the spans, each of them is spawned as an empty span.
+fn pause_count(spans: &[Span]) -> usize {
+    spans
+        .iter()
+        .filter(|span| matches!(span, Span::Pause(_)))
+        .count()
+}
+
+/// The spans of the blocks, if they only consist of inline text.
+fn inline_spans(mut blocks: Vec<Block>) -> Option<Vec<Span>> {
+    match blocks.pop() {
+        None => Some(Vec::new()),
+        Some(Block::Text(block)) if blocks.is_empty() => Some(block.spans),
+        Some(_) => None,
+    }
+}
+
+/// Collect the color names of the spans that are neither registered in the color map nor CSS colors.
+fn collect_unknown_colors(spans: &[Span], spawn_context: &mut SpawnContext) {
+    for span in spans {
+        let Span::Text { context, .. } = span else {
+            continue;
+        };
+        let gradient = context.gradient.as_ref();
+
+        for color in [
+            Some(&context.color),
+            context.background.as_ref(),
+            gradient.map(|gradient| &gradient.from),
+            gradient.map(|gradient| &gradient.to),
+            context.outline.as_ref().map(|outline| &outline.color),
+            context.shadow.as_ref().map(|shadow| &shadow.color),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if let BbCodeColor::Named(name) = color {
+                if color.resolve(spawn_context.color_scope).is_none() {
+                    spawn_context.unknown_colors.insert(name.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Spawn a text span with the style of the context.
+fn spawn_span<'a>(
+    builder: &'a mut ChildBuilder,
+    text: &str,
+    text_font: TextFont,
+    context: &BbcodeContext,
+    settings: &BbcodeSettings,
+    color_scope: ColorScope,
+) -> EntityCommands<'a> {
+    let mut text_commands = builder.spawn((
+        TextSpan::new(text),
+        text_font,
+        TextColor(context.resolve_color(color_scope)),
+    ));
+
+    // Track named colors for efficient update, gradients are tracked per character
+    if let (BbCodeColor::Named(name), None) = (&context.color, &context.gradient) {
+        text_commands.insert(BbCodeColored {
+            name: name.clone(),
+            fallbacks: context.inherited_colors.clone(),
+            modifiers: context.color_modifiers.clone(),
+        });
+    }
+
+    if let Some(background) = &context.background {
+        text_commands.insert(TextBackground(
+            background.resolve(color_scope).unwrap_or(Color::NONE),
+        ));
+
+        if let BbCodeColor::Named(name) = background {
+            text_commands.insert(BbCodeBackgroundColored { name: name.clone() });
+        }
+    }
+
+    if let Some(tooltip) = &context.tooltip {
+        text_commands.insert(BbcodeTooltip {
+            content: tooltip.clone(),
+        });
+    }
+
+    if context.reveal_speed != 1. {
+        text_commands.insert(RevealSpeed(context.reveal_speed));
+    }
+
+    context.effects.insert(&mut text_commands);
+
+    // Apply marker components
+    for marker in &context.markers {
+        if let Some(modifier) = settings.modifiers.modifier_map.get(marker) {
+            modifier(&mut text_commands);
+        }
+    }
+
+    text_commands
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::{BbcodeColorOverrides, BbcodePlugin, BbcodeSet, TextOutline};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<UiScale>()
+            .add_plugins(BbcodePlugin::new());
+        app
+    }
+
+    /// The texts of the spans below the given entity, in order.
+    fn span_texts(app: &App, entity: Entity) -> Vec<String> {
+        let world = app.world();
+        let mut texts = Vec::new();
+
+        if let Some(children) = world.get::<Children>(entity) {
+            for &child in children {
+                if let Some(span) = world.get::<TextSpan>(child) {
+                    texts.push(span.0.clone());
+                }
+                texts.extend(span_texts(app, child));
+            }
+        }
+
+        texts
+    }
+
+    #[test]
+    fn single_block_in_root_text() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new("test [b]bold[/b] text"))
+            .id();
+
+        app.update();
+
+        assert_eq!(span_texts(&app, entity), ["test ", "bold", " text"]);
+        let children = app.world().get::<Children>(entity).unwrap();
+        assert!(children
+            .iter()
+            .all(|&child| app.world().get::<TextSpan>(child).is_some()));
+    }
+
+    #[test]
+    fn single_alignment_in_root_text() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new("[center]centered[/center]"))
+            .id();
+
+        app.update();
+
+        assert_eq!(
+            app.world().get::<TextLayout>(entity).unwrap().justify,
+            JustifyText::Center
+        );
+        assert_eq!(span_texts(&app, entity), ["centered"]);
+
+        // The layout of the user is restored when the alignment is removed
+        app.world_mut().get_mut::<Bbcode>(entity).unwrap().content = "plain".into();
+        app.update();
+
+        assert_eq!(
+            app.world().get::<TextLayout>(entity).unwrap().justify,
+            JustifyText::Left
+        );
+    }
+
+    #[test]
+    fn block_layout_is_restored_on_rebuild() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                Bbcode::new("[center]a[/center]\n[right]b[/right]"),
+                TextLayout::new_with_justify(JustifyText::Right),
+                Node {
+                    flex_direction: FlexDirection::RowReverse,
+                    ..default()
+                },
+            ))
+            .id();
+
+        app.update();
+
+        let node = app.world().get::<Node>(entity).unwrap();
+        assert_eq!(node.flex_direction, FlexDirection::Column);
+
+        // Blocks without an alignment use the layout of the user
+        app.world_mut().get_mut::<Bbcode>(entity).unwrap().content = "[h1]a[/h1]\nb".into();
+        app.update();
+
+        let children = app.world().get::<Children>(entity).unwrap().to_vec();
+        assert_eq!(
+            app.world().get::<TextLayout>(children[1]).unwrap().justify,
+            JustifyText::Right
+        );
+
+        app.world_mut().get_mut::<Bbcode>(entity).unwrap().content = "[center]a[/center]".into();
+        app.update();
+
+        assert_eq!(
+            app.world().get::<TextLayout>(entity).unwrap().justify,
+            JustifyText::Center
+        );
+
+        app.world_mut().get_mut::<Bbcode>(entity).unwrap().content = "plain".into();
+        app.update();
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<TextLayout>(entity).unwrap().justify,
+            JustifyText::Right
+        );
+        let node = world.get::<Node>(entity).unwrap();
+        assert_eq!(node.flex_direction, FlexDirection::RowReverse);
+        assert_eq!(node.row_gap, Val::Px(0.));
+    }
+
+    #[test]
+    fn multiple_alignments_split_blocks() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[center]centered[/center]\n[right]right [b]bold[/b][/right]",
+            ))
+            .id();
+
+        app.update();
+
+        let children = app.world().get::<Children>(entity).unwrap().to_vec();
+        let justify: Vec<_> = children
+            .iter()
+            .map(|&child| app.world().get::<TextLayout>(child).unwrap().justify)
+            .collect();
+
+        assert_eq!(justify, [JustifyText::Center, JustifyText::Right]);
+        assert_eq!(span_texts(&app, children[0]), ["centered"]);
+        assert_eq!(span_texts(&app, children[1]), ["right ", "bold"]);
+    }
+
+    #[test]
+    fn headings_rules_and_quotes_are_blocks() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[h1]Title[/h1]\nSome text\n[hr]\n[quote=Alice]quoted[/quote]",
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        let children = world.get::<Children>(entity).unwrap().to_vec();
+        assert_eq!(children.len(), 4);
+
+        assert_eq!(span_texts(&app, children[0]), ["Title"]);
+        let heading_span = world.get::<Children>(children[0]).unwrap()[0];
+        assert_eq!(world.get::<TextFont>(heading_span).unwrap().font_size, 40.);
+
+        assert_eq!(span_texts(&app, children[1]), ["Some text"]);
+
+        assert!(world.get::<Text>(children[2]).is_none());
+        assert!(world.get::<BackgroundColor>(children[2]).is_some());
+
+        let quote_children = world.get::<Children>(children[3]).unwrap().to_vec();
+        assert_eq!(span_texts(&app, quote_children[0]), ["Alice:"]);
+        assert_eq!(span_texts(&app, quote_children[1]), ["quoted"]);
+    }
+
+    #[test]
+    fn lists_have_markers_and_nest() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[list=1]\n[*]one\n[*]two\n[list][*]nested[/list]\n[/list]",
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        let children = world.get::<Children>(entity).unwrap().to_vec();
+        assert_eq!(children.len(), 1);
+
+        let items = world.get::<Children>(children[0]).unwrap().to_vec();
+        assert_eq!(items.len(), 2);
+
+        let first_item = world.get::<Children>(items[0]).unwrap().to_vec();
+        assert_eq!(span_texts(&app, first_item[0]), ["1."]);
+        assert_eq!(span_texts(&app, first_item[1]), ["one"]);
+
+        let second_item = world.get::<Children>(items[1]).unwrap().to_vec();
+        assert_eq!(span_texts(&app, second_item[0]), ["2."]);
+        assert_eq!(span_texts(&app, second_item[1]), ["two", "◦", "nested"]);
+    }
+
+    #[test]
+    fn tables_are_grids() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[table border=1][tr][td]a[/td][td]b[/td][/tr][tr][td colspan=2]wide[/td][/tr][/table]",
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        let children = world.get::<Children>(entity).unwrap().to_vec();
+        assert_eq!(children.len(), 1);
+
+        let table = world.get::<Node>(children[0]).unwrap();
+        assert_eq!(table.display, Display::Grid);
+        assert_eq!(
+            table.grid_template_columns,
+            RepeatedGridTrack::auto::<Vec<_>>(2)
+        );
+
+        let cells = world.get::<Children>(children[0]).unwrap().to_vec();
+        assert_eq!(cells.len(), 3);
+
+        let wide_cell = world.get::<Node>(cells[2]).unwrap();
+        assert_eq!(wide_cell.grid_row, GridPlacement::start(2));
+        assert_eq!(wide_cell.grid_column, GridPlacement::start_span(1, 2));
+        assert_eq!(wide_cell.border.right, Val::Px(1.));
+        assert_eq!(span_texts(&app, cells[2]), ["wide"]);
+    }
+
+    #[test]
+    fn code_blocks_keep_raw_content() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "Example:\n[code]\n[b]not bold[/b]\n  indented\n[/code]",
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        let children = world.get::<Children>(entity).unwrap().to_vec();
+        assert_eq!(children.len(), 2);
+
+        assert!(world.get::<BackgroundColor>(children[1]).is_some());
+        assert_eq!(
+            span_texts(&app, children[1]),
+            ["[b]not bold[/b]\n  indented"]
+        );
+    }
+
+    #[test]
+    fn backgrounds_use_named_colors() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[bg=#ff0000]red[/bg] and [highlight=new]new[/highlight]",
+            ))
+            .id();
+
+        app.update();
+
+        // The text is wrapped, so the backgrounds can be drawn next to it
+        let world = app.world();
+        let wrapper = world.get::<Children>(entity).unwrap()[0];
+        let text = world.get::<Children>(wrapper).unwrap()[0];
+        assert!(world.get::<TextHighlights>(text).is_some());
+
+        let spans = world.get::<Children>(text).unwrap().to_vec();
+        assert_eq!(
+            world.get::<TextBackground>(spans[0]).unwrap().0,
+            Color::srgb(1., 0., 0.)
+        );
+        assert_eq!(
+            world.get::<TextBackground>(spans[2]).unwrap().0,
+            Color::NONE
+        );
+
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("new", Color::srgb(0., 1., 0.));
+        app.update();
+
+        assert_eq!(
+            app.world().get::<TextBackground>(spans[2]).unwrap().0,
+            Color::srgb(0., 1., 0.)
+        );
+    }
+
+    #[test]
+    fn shadows_and_outlines_are_layers() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                Bbcode::new("[shadow=#000000 x=3 y=4]shadow[/shadow] text"),
+                BbcodeSettings::default().with_outline(TextOutline::default()),
+            ))
+            .id();
+
+        app.update();
+
+        // One shadow layer and eight outline layers below the text
+        let world = app.world();
+        let wrapper = world.get::<Children>(entity).unwrap()[0];
+        let layers = world.get::<Children>(wrapper).unwrap().to_vec();
+        assert_eq!(layers.len(), 10);
+
+        let shadow = world.get::<Node>(layers[0]).unwrap();
+        assert_eq!(shadow.position_type, PositionType::Absolute);
+        assert_eq!((shadow.left, shadow.top), (Val::Px(3.), Val::Px(4.)));
+
+        let shadow_spans = world.get::<Children>(layers[0]).unwrap().to_vec();
+        let text_spans = world.get::<Children>(layers[9]).unwrap().to_vec();
+        assert_eq!(span_texts(&app, layers[0]), ["shadow", " text"]);
+        assert_eq!(
+            world.get::<TextColor>(shadow_spans[0]).unwrap().0,
+            Color::srgb(0., 0., 0.)
+        );
+        assert_eq!(
+            world.get::<TextColor>(shadow_spans[1]).unwrap().0,
+            Color::NONE
+        );
+        assert_eq!(
+            world.get::<LayerSpan>(shadow_spans[1]).unwrap().source,
+            text_spans[1]
+        );
+
+        // The layers mirror changes to the text, e.g. when it's revealed
+        app.world_mut()
+            .get_mut::<TextSpan>(text_spans[1])
+            .unwrap()
+            .0 = " changed".into();
+        app.update();
+
+        assert_eq!(
+            app.world().get::<TextSpan>(shadow_spans[1]).unwrap().0,
+            " changed"
+        );
+    }
+
+    #[test]
+    fn split_glyphs_are_indexed_across_spans() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                Bbcode::new("ab [wave]cd [b]ef[/b][/wave]"),
+                BbcodeSettings::default().with_split_glyphs(true),
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        let spans = world.get::<Children>(entity).unwrap().to_vec();
+        assert_eq!(span_texts(&app, entity), ["ab ", "c", "d", " ", "e", "f"]);
+        assert!(world.get::<GlyphIndex>(spans[0]).is_none());
+
+        // The indices continue across the spans, so the effects don't jump at style changes
+        let indices: Vec<usize> = spans[1..]
+            .iter()
+            .map(|&span| world.get::<GlyphIndex>(span).unwrap().0)
+            .collect();
+        assert_eq!(indices, [3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn translations_are_inserted() {
+        let mut app = app();
+        app.insert_resource(BbcodeTranslations::new(
+            |key: &str, args: &HashMap<&str, &str>| match key {
+                "greeting" => Some("Hello [b]World[/b]".to_string()),
+                "items" => Some(format!("{} [i]items[/i]", args.get("count")?)),
+                "loop" => Some("a[t=loop]".to_string()),
+                _ => None,
+            },
+        ));
+
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[t=greeting]! [t=items count=3] [t=missing] [t=items]",
+            ))
+            .id();
+
+        app.update();
+
+        // Missing translations show the key instead
+        assert_eq!(
+            span_texts(&app, entity),
+            ["Hello ", "World", "! ", "3 ", "items", " ", "missing", " ", "items"]
+        );
+
+        // Translations including themselves stop at the maximum depth
+        app.world_mut().get_mut::<Bbcode>(entity).unwrap().content = "[t=loop]".into();
+        app.update();
+
+        let mut expected = vec!["a"; MAX_TRANSLATION_DEPTH as usize];
+        expected.push("loop");
+        assert_eq!(span_texts(&app, entity), expected);
+    }
+
+    #[test]
+    fn text_is_rebuilt_when_translations_change() {
+        let mut app = app();
+        app.insert_resource(BbcodeTranslations::new(
+            |_: &str, _: &HashMap<&str, &str>| Some("Start".to_string()),
+        ));
+
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new("[b][t=menu.start][/b]"))
+            .id();
+
+        app.update();
+        assert_eq!(span_texts(&app, entity), ["Start"]);
+
+        app.world_mut()
+            .resource_mut::<BbcodeTranslations>()
+            .set_translator(|_: &str, _: &HashMap<&str, &str>| Some("Starten".to_string()));
+        app.update();
+
+        assert_eq!(span_texts(&app, entity), ["Starten"]);
+    }
+
+    #[test]
+    fn unregistered_colors_fall_back_to_inherited_color() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                Bbcode::new("[c=secondary]outer [c=primary]inner[/c][/c]"),
+                BbcodeSettings::new("", 20., Color::srgb(1., 0., 0.)),
+            ))
+            .id();
+
+        app.update();
+
+        let spans = app.world().get::<Children>(entity).unwrap().to_vec();
+        let span_color =
+            |app: &App, index: usize| app.world().get::<TextColor>(spans[index]).unwrap().0;
+        assert_eq!(span_color(&app, 1), Color::srgb(1., 0., 0.));
+
+        // Registering an outer color updates the fallback
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("secondary", Color::srgb(0., 0., 1.));
+        app.update();
+
+        assert_eq!(span_color(&app, 0), Color::srgb(0., 0., 1.));
+        assert_eq!(span_color(&app, 1), Color::srgb(0., 0., 1.));
+
+        // Registering the color itself updates the text
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("primary", Color::srgb(0., 1., 0.));
+        app.update();
+
+        assert_eq!(span_color(&app, 0), Color::srgb(0., 0., 1.));
+        assert_eq!(span_color(&app, 1), Color::srgb(0., 1., 0.));
+    }
+
+    #[test]
+    fn color_modifiers_are_kept_on_update() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                Bbcode::new("[c=primary alpha=0.5]alpha[/c] [fade=0.5]faded[/fade]"),
+                BbcodeSettings::new("", 20., Color::srgb(1., 0., 0.)),
+            ))
+            .id();
+
+        app.update();
+
+        let spans = app.world().get::<Children>(entity).unwrap().to_vec();
+        let span_color =
+            |app: &App, index: usize| app.world().get::<TextColor>(spans[index]).unwrap().0;
+        // The modifiers are applied to the inherited color while the name is not registered
+        assert_eq!(span_color(&app, 0), Color::srgba(1., 0., 0., 0.5));
+        assert_eq!(span_color(&app, 2), Color::srgba(1., 0., 0., 0.5));
+
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("primary", Color::srgb(0., 1., 0.));
+        app.update();
+
+        assert_eq!(span_color(&app, 0), Color::srgba(0., 1., 0., 0.5));
+    }
+
+    #[test]
+    fn color_modifiers_apply_to_nested_colors_in_order() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[fade=0.5]a[c=#00ff00]b[/c][c=#00ff00 alpha=0.8 darken=1]c[/c][/fade]\
+                [c=#000000 darken=1 alpha=0.8 lighten=1]d[/c]",
+            ))
+            .id();
+
+        app.update();
+
+        let spans = app.world().get::<Children>(entity).unwrap().to_vec();
+        let span_color =
+            |app: &App, index: usize| app.world().get::<TextColor>(spans[index]).unwrap().0;
+        // The surrounding fade also applies to nested colors
+        assert_eq!(span_color(&app, 0).alpha(), 0.5);
+        assert_eq!(span_color(&app, 1), Color::srgba(0., 1., 0., 0.5));
+        assert_eq!(span_color(&app, 2).alpha(), 0.4);
+        assert_eq!(span_color(&app, 2).luminance(), Color::BLACK.luminance());
+        // The modifiers are applied in the order they are written
+        assert!(span_color(&app, 3).luminance() > 0.99);
+        assert_eq!(span_color(&app, 3).alpha(), 0.8);
+    }
+
+    #[test]
+    fn gradients_color_each_character() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[gradient from=#ff0000 to=end]a[b]b[/b]c[/gradient]",
+            ))
+            .id();
+
+        app.update();
+
+        let spans = app.world().get::<Children>(entity).unwrap().to_vec();
+        assert_eq!(span_texts(&app, entity), ["a", "b", "c"]);
+
+        let assert_color = |app: &App, index: usize, expected: Srgba| {
+            let color = app.world().get::<TextColor>(spans[index]).unwrap().0;
+            assert!((color.to_srgba().to_vec4() - expected.to_vec4()).length() < 1e-4);
+        };
+        // The unregistered end falls back to the surrounding color
+        assert_color(&app, 0, Srgba::rgb(1., 0., 0.));
+        assert_color(&app, 1, Srgba::rgb(1., 0.5, 0.5));
+        assert_color(&app, 2, Srgba::WHITE);
+
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("end", Color::srgb(0., 0., 1.));
+        app.update();
+
+        assert_color(&app, 1, Srgba::rgb(0.5, 0., 0.5));
+        assert_color(&app, 2, Srgba::rgb(0., 0., 1.));
+    }
+
+    #[test]
+    fn spans_can_be_queried_after_conversion() {
+        #[derive(Default, Resource)]
+        struct SpanCount(usize);
+
+        let mut app = app();
+        app.init_resource::<SpanCount>().add_systems(
+            Update,
+            (
+                (|mut commands: Commands| {
+                    commands.spawn(Bbcode::new("[b]bold[/b] text"));
+                })
+                .before(BbcodeSet::Convert),
+                (|span_query: Query<&TextSpan>, mut span_count: ResMut<SpanCount>| {
+                    span_count.0 = span_query.iter().count();
+                })
+                .after(BbcodeSet::Convert),
+            ),
+        );
+
+        app.update();
+
+        // The spans are spawned in the same frame
+        assert_eq!(app.world().resource::<SpanCount>().0, 2);
+    }
+
+    #[test]
+    fn rebuilt_event_maps_markers_to_spans() {
+        #[derive(Default, Resource)]
+        struct Rebuilt(Vec<BbcodeRebuilt>);
+
+        let mut app = app();
+        app.init_resource::<Rebuilt>().add_systems(
+            Update,
+            (|mut events: EventReader<BbcodeRebuilt>, mut rebuilt: ResMut<Rebuilt>| {
+                rebuilt.0.extend(events.read().cloned());
+            })
+            .after(BbcodeSet::Convert),
+        );
+
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[m=hp]10[/m] of [pause=1][m=hp]20 [b]HP[/b][/m] [shadow]text[/shadow]",
+            ))
+            .id();
+
+        app.update();
+
+        let rebuilt = &app.world().resource::<Rebuilt>().0;
+        assert_eq!(rebuilt.len(), 1);
+        assert_eq!(rebuilt[0].entity, entity);
+        // The copies for the shadow are not included
+        assert_eq!(rebuilt[0].span_count, 6);
+
+        let hp_spans = &rebuilt[0].markers["hp"];
+        let texts: Vec<&str> = hp_spans
+            .iter()
+            .map(|&span| app.world().get::<TextSpan>(span).unwrap().0.as_str())
+            .collect();
+        assert_eq!(texts, ["10", "20 ", "HP"]);
+
+        // The previous spans are removed when the content fails to parse
+        app.world_mut().get_mut::<Bbcode>(entity).unwrap().content = "[b]unclosed".into();
+        app.update();
+
+        let rebuilt = &app.world().resource::<Rebuilt>().0;
+        assert_eq!(rebuilt.len(), 2);
+        assert_eq!(rebuilt[1].span_count, 0);
+        assert!(rebuilt[1].markers.is_empty());
+    }
+
+    #[test]
+    fn spans_of_named_tags_can_be_looked_up() {
+        let mut app = app();
+
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[m=hp]10[/m] [m=hp][/m] [gradient][m=hp]20 [b]HP[/b][/m][/gradient] \
+                [c=red id=name]Alice[/c] [outline][id=title]The [m=hp]Brave[/m][/id][/outline]",
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        let spans = world.get::<BbcodeSpans>(entity).unwrap();
+        let texts = |entities: &[Entity]| -> String {
+            entities
+                .iter()
+                .map(|&span| world.get::<TextSpan>(span).unwrap().0.as_str())
+                .collect()
+        };
+
+        assert_eq!(spans.marker_occurrences("hp").len(), 4);
+        assert_eq!(texts(spans.marker("hp", 0).unwrap()), "10");
+        assert_eq!(spans.marker("hp", 1), Some(&[][..]));
+        // The gradient is split into characters
+        assert_eq!(spans.marker("hp", 2).unwrap().len(), 5);
+        assert_eq!(texts(spans.marker("hp", 2).unwrap()), "20 HP");
+        // The copies for the outline are not included
+        assert_eq!(texts(spans.marker("hp", 3).unwrap()), "Brave");
+        assert_eq!(spans.marker("hp", 4), None);
+
+        assert_eq!(texts(spans.id("name").unwrap()), "Alice");
+        assert_eq!(texts(spans.id("title").unwrap()), "The Brave");
+        assert_eq!(spans.id("missing"), None);
+    }
+
+    #[test]
+    fn color_overrides_apply_to_descendants() {
+        const RED: Color = Color::srgb(1., 0., 0.);
+        const GREEN: Color = Color::srgb(0., 1., 0.);
+        const BLUE: Color = Color::srgb(0., 0., 1.);
+
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("accent", RED);
+
+        let global = app
+            .world_mut()
+            .spawn(Bbcode::new("[c=accent]global[/c]"))
+            .id();
+        let card = app
+            .world_mut()
+            .spawn(BbcodeColorOverrides::new().with_color("accent", GREEN))
+            .id();
+        let scoped = app
+            .world_mut()
+            .spawn(Bbcode::new("[c=accent]scoped[/c]"))
+            .set_parent(card)
+            .id();
+
+        app.update();
+
+        let span_color = |app: &App, entity: Entity| {
+            let span = app.world().get::<Children>(entity).unwrap()[0];
+            app.world().get::<TextColor>(span).unwrap().0
+        };
+        assert_eq!(span_color(&app, global), RED);
+        assert_eq!(span_color(&app, scoped), GREEN);
+
+        // Global changes don't affect overridden names
+        app.world_mut()
+            .resource_mut::<ColorMap>()
+            .insert("accent", BLUE);
+        app.update();
+
+        assert_eq!(span_color(&app, global), BLUE);
+        assert_eq!(span_color(&app, scoped), GREEN);
+
+        // Changing the overrides updates the text below
+        app.world_mut()
+            .get_mut::<BbcodeColorOverrides>(card)
+            .unwrap()
+            .insert("accent", RED);
+        app.update();
+
+        assert_eq!(span_color(&app, scoped), RED);
+
+        app.world_mut()
+            .entity_mut(card)
+            .remove::<BbcodeColorOverrides>();
+        app.update();
+
+        assert_eq!(span_color(&app, scoped), BLUE);
+    }
+}
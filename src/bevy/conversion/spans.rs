@@ -0,0 +1,156 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::bbcode::BbcodeTag;
+
+/// The spans of the named tags of a [`Bbcode`](crate::Bbcode) entity, inserted on the entity when it's converted.
+///
+/// Tags are named by a marker, e.g. `[m=hp]`, or by an `id` parameter, e.g. `[id=title]` or `[c=red id=title]`.
+/// Each occurrence of a name is numbered in the order of the text, starting at `0`,
+/// so the spans of a specific tag can be looked up directly:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_mod_bbcode::BbcodeSpans;
+/// fn update_second_hp(spans_query: Query<&BbcodeSpans>, mut span_query: Query<&mut TextSpan>) {
+///     for spans in spans_query.iter() {
+///         let Some(&[entity]) = spans.marker("hp", 1) else {
+///             continue;
+///         };
+///
+///         if let Ok(mut span) = span_query.get_mut(entity) {
+///             span.0 = "42".to_string();
+///         }
+///     }
+/// }
+/// ```
+///
+/// The copies of the text for outlines and shadows are not included.
+#[derive(Debug, Clone, Default, Component)]
+pub struct BbcodeSpans {
+    /// The spans of each `[m]` tag, by marker name and occurrence.
+    markers: HashMap<String, Vec<Vec<Entity>>>,
+    /// The spans of each tag with an `id`, by id and occurrence.
+    ids: HashMap<String, Vec<Vec<Entity>>>,
+}
+
+impl BbcodeSpans {
+    /// The spans of all named tags, which are recorded while spawning them.
+    pub(super) fn new(tags: &TagCounter) -> Self {
+        let mut spans = Self::default();
+
+        for (name, &count) in &tags.counts {
+            let (map, name) = spans.map_mut(name);
+            map.insert(name.to_string(), vec![Vec::new(); count]);
+        }
+
+        spans
+    }
+
+    /// Add the spawned spans to the tag.
+    pub(super) fn record(&mut self, tag: &TagOccurrence, entities: &[Entity]) {
+        let (map, name) = self.map_mut(&tag.name);
+
+        if let Some(spans) = map
+            .get_mut(name)
+            .and_then(|occurrences| occurrences.get_mut(tag.index))
+        {
+            spans.extend(entities);
+        }
+    }
+
+    /// The spans of the `[m]` tag with the given name and index.
+    ///
+    /// For example, `spans.marker("hp", 2)` returns the spans of the third `[m=hp]` tag.
+    pub fn marker(&self, name: &str, index: usize) -> Option<&[Entity]> {
+        self.marker_occurrences(name).get(index).map(Vec::as_slice)
+    }
+
+    /// The spans of all `[m]` tags with the given name, in the order of the text.
+    pub fn marker_occurrences(&self, name: &str) -> &[Vec<Entity>] {
+        self.markers.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// The spans of the first tag with the given `id`.
+    pub fn id(&self, id: &str) -> Option<&[Entity]> {
+        self.id_occurrences(id).first().map(Vec::as_slice)
+    }
+
+    /// The spans of all tags with the given `id`, in the order of the text.
+    pub fn id_occurrences(&self, id: &str) -> &[Vec<Entity>] {
+        self.ids.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    fn map_mut<'a>(
+        &mut self,
+        name: &'a TagName,
+    ) -> (&mut HashMap<String, Vec<Vec<Entity>>>, &'a str) {
+        match name {
+            TagName::Marker(name) => (&mut self.markers, name),
+            TagName::Id(id) => (&mut self.ids, id),
+        }
+    }
+}
+
+/// The name of a tag whose spans are recorded in [`BbcodeSpans`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) enum TagName {
+    /// The name of an `[m]` tag.
+    Marker(String),
+    /// The `id` of a tag.
+    Id(String),
+}
+
+impl TagName {
+    /// The names of the tag, from a marker and an `id`.
+    fn of(tag: &BbcodeTag) -> Vec<Self> {
+        let marker = match tag.name() {
+            "m" | "marker" => tag.simple_param().as_deref().map(Self::marker),
+            _ => None,
+        };
+        let id = match tag.name() {
+            "id" => tag.simple_param().as_deref(),
+            _ => tag.param("id"),
+        };
+
+        marker.into_iter().chain(id.map(Self::id)).collect()
+    }
+
+    fn marker(name: &str) -> Self {
+        Self::Marker(name.to_string())
+    }
+
+    fn id(id: &str) -> Self {
+        Self::Id(id.to_string())
+    }
+}
+
+/// An occurrence of a named tag, e.g. the second `[m=hp]` tag of the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct TagOccurrence {
+    pub(super) name: TagName,
+    /// The number of tags with the same name before this one.
+    pub(super) index: usize,
+}
+
+/// Numbers the named tags in the order of the text.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TagCounter {
+    /// The number of occurrences of each name so far.
+    counts: HashMap<TagName, usize>,
+}
+
+impl TagCounter {
+    /// Count the names of the tag and return their occurrences.
+    pub(super) fn count(&mut self, tag: &BbcodeTag) -> Vec<TagOccurrence> {
+        TagName::of(tag)
+            .into_iter()
+            .map(|name| {
+                let count = self.counts.entry(name.clone()).or_default();
+                let index = *count;
+                *count += 1;
+
+                TagOccurrence { name, index }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,448 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    text::{ComputedTextBlock, TextLayoutInfo},
+    ui::UiSystem,
+    window::PrimaryWindow,
+};
+
+use super::{layer::LayerSpan, plugin::BbcodeSet};
+use crate::bbcode::BbcodeTag;
+
+pub struct EffectPlugin;
+
+impl Plugin for EffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (animate_rainbow, animate_pulse, animate_glyph_offsets).in_set(BbcodeSet::Effects),
+        )
+        // The glyphs are only positioned once the text layout has been computed
+        .add_systems(PostUpdate, animate_glyphs.after(UiSystem::PostLayout));
+    }
+}
+
+/// Moves the characters of the text up and down in a wave.
+///
+/// Inserted on the `TextSpan`s inside of a `[wave]` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct WaveEffect {
+    /// The height of the wave in logical pixels.
+    pub amplitude: f32,
+    /// The number of waves per second.
+    pub frequency: f32,
+}
+
+impl Default for WaveEffect {
+    fn default() -> Self {
+        Self {
+            amplitude: 4.,
+            frequency: 1.,
+        }
+    }
+}
+
+/// Randomly shakes the characters of the text.
+///
+/// Inserted on the `TextSpan`s inside of a `[shake]` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct ShakeEffect {
+    /// The maximum distance of a character from its original position, in logical pixels.
+    pub strength: f32,
+    /// The number of times per second the characters move to a new position.
+    pub rate: f32,
+}
+
+impl Default for ShakeEffect {
+    fn default() -> Self {
+        Self {
+            strength: 2.,
+            rate: 20.,
+        }
+    }
+}
+
+/// Cycles the color of the text through all hues.
+///
+/// Inserted on the `TextSpan`s inside of a `[rainbow]` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct RainbowEffect {
+    /// The number of full hue cycles per second.
+    pub frequency: f32,
+    /// The saturation of the colors.
+    pub saturation: f32,
+    /// The value (brightness) of the colors.
+    pub value: f32,
+}
+
+impl Default for RainbowEffect {
+    fn default() -> Self {
+        Self {
+            frequency: 0.2,
+            saturation: 0.8,
+            value: 1.,
+        }
+    }
+}
+
+/// Fades the text in and out.
+///
+/// Inserted on the `TextSpan`s inside of a `[pulse]` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct PulseEffect {
+    /// The number of pulses per second.
+    pub frequency: f32,
+    /// The lowest alpha value of the text color.
+    pub min_alpha: f32,
+}
+
+impl Default for PulseEffect {
+    fn default() -> Self {
+        Self {
+            frequency: 1.,
+            min_alpha: 0.2,
+        }
+    }
+}
+
+/// The animated text effects to apply to a text span.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct TextEffects {
+    pub(crate) wave: Option<WaveEffect>,
+    pub(crate) shake: Option<ShakeEffect>,
+    pub(crate) rainbow: Option<RainbowEffect>,
+    pub(crate) pulse: Option<PulseEffect>,
+}
+
+impl TextEffects {
+    /// Enable the effect of the given tag, if it's an effect tag.
+    ///
+    /// Returns `true` if the tag was handled.
+    pub(crate) fn apply_tag(&mut self, tag: &BbcodeTag) -> bool {
+        match tag.name() {
+            "wave" => {
+                let default = WaveEffect::default();
+                self.wave = Some(WaveEffect {
+                    amplitude: parse_param(tag, "amp", default.amplitude),
+                    frequency: parse_param(tag, "freq", default.frequency),
+                });
+            }
+            "shake" => {
+                let default = ShakeEffect::default();
+                self.shake = Some(ShakeEffect {
+                    strength: parse_param(tag, "strength", default.strength),
+                    rate: parse_param(tag, "rate", default.rate),
+                });
+            }
+            "rainbow" => {
+                let default = RainbowEffect::default();
+                self.rainbow = Some(RainbowEffect {
+                    frequency: parse_param(tag, "freq", default.frequency),
+                    saturation: parse_param(tag, "sat", default.saturation),
+                    value: parse_param(tag, "val", default.value),
+                });
+            }
+            "pulse" => {
+                let default = PulseEffect::default();
+                self.pulse = Some(PulseEffect {
+                    frequency: parse_param(tag, "freq", default.frequency),
+                    min_alpha: parse_param(tag, "min", default.min_alpha),
+                });
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Whether any effect is enabled.
+    pub(crate) fn is_any(&self) -> bool {
+        self.wave.is_some()
+            || self.shake.is_some()
+            || self.rainbow.is_some()
+            || self.pulse.is_some()
+    }
+
+    /// Whether the effects move the individual glyphs of the text.
+    pub(crate) fn moves_glyphs(&self) -> bool {
+        self.wave.is_some() || self.shake.is_some()
+    }
+
+    /// Insert the components for the enabled effects.
+    pub(crate) fn insert(&self, commands: &mut EntityCommands) {
+        if let Some(wave) = self.wave {
+            commands.insert(wave);
+        }
+        if let Some(shake) = self.shake {
+            commands.insert(shake);
+        }
+        if let Some(rainbow) = self.rainbow {
+            commands.insert(rainbow);
+        }
+        if let Some(pulse) = self.pulse {
+            commands.insert(pulse);
+        }
+    }
+}
+
+/// Parse the complex parameter with the given key, falling back to the default value.
+fn parse_param(tag: &BbcodeTag, key: &str, default: f32) -> f32 {
+    match tag.param(key).map(|value| value.trim().parse()) {
+        Some(Ok(value)) => value,
+        Some(Err(_)) => {
+            warn!("Invalid {key} parameter on [{}] tag", tag.name());
+            default
+        }
+        None => default,
+    }
+}
+
+/// Tracks the glyph positions of text with moving glyphs.
+///
+/// Inserted on the root text entity.
+#[derive(Debug, Clone, Default, Component)]
+pub(crate) struct AnimatedGlyphs {
+    /// The positions of the glyphs as computed by the text layout.
+    base_positions: Vec<Vec2>,
+}
+
+/// The offset of a text span from its laid out position, in logical pixels.
+///
+/// Inserted on the `TextSpan`s of text split into single characters, see [`BbcodeSettings::with_split_glyphs`](crate::BbcodeSettings::with_split_glyphs).
+/// The text layout is not affected by the offset, so you can freely animate it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Component, Deref, DerefMut)]
+pub struct GlyphOffset(pub Vec2);
+
+/// The index of the character of a split text span within its text block, counted across all spans.
+#[derive(Debug, Clone, Copy, Component)]
+pub(crate) struct GlyphIndex(pub(crate) usize);
+
+fn animate_rainbow(
+    time: Res<Time>,
+    mut query: Query<(&RainbowEffect, &mut TextColor, Option<&GlyphIndex>), Without<LayerSpan>>,
+) {
+    for (rainbow, mut text_color, glyph_index) in query.iter_mut() {
+        // Shift the hue of split characters to color them differently
+        let hue_shift = glyph_index.map_or(0., |glyph_index| glyph_index.0 as f32 * 20.);
+        let hue = (time.elapsed_secs() * rainbow.frequency * 360. + hue_shift) % 360.;
+        text_color.0 = Hsva::hsv(hue, rainbow.saturation, rainbow.value).into();
+    }
+}
+
+/// The alpha value of a pulsing text span without the pulse.
+#[derive(Debug, Clone, Copy, Component)]
+struct PulseAlpha {
+    /// The alpha value of the text color, e.g. set via a color modifier.
+    base: f32,
+    /// The alpha value set by the pulse, to detect when the color has been changed by other systems.
+    applied: f32,
+}
+
+/// Fade the pulsing text in and out.
+///
+/// The copies of the text for outlines and shadows pulse as well, so they don't stay visible.
+fn animate_pulse(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &PulseEffect,
+        &mut TextColor,
+        Option<&mut PulseAlpha>,
+    )>,
+) {
+    for (entity, pulse, mut text_color, pulse_alpha) in query.iter_mut() {
+        let factor = 0.5 + 0.5 * (time.elapsed_secs() * pulse.frequency * TAU).cos();
+        let pulse_factor = pulse.min_alpha + (1. - pulse.min_alpha) * factor;
+
+        // Multiply the alpha value of the color, which can be changed by color modifiers
+        let alpha = text_color.0.alpha();
+        let base = match &pulse_alpha {
+            Some(pulse_alpha) if pulse_alpha.applied == alpha => pulse_alpha.base,
+            _ => alpha,
+        };
+
+        let applied = base * pulse_factor;
+        text_color.0.set_alpha(applied);
+
+        match pulse_alpha {
+            Some(mut pulse_alpha) => *pulse_alpha = PulseAlpha { base, applied },
+            None => {
+                commands.entity(entity).insert(PulseAlpha { base, applied });
+            }
+        }
+    }
+}
+
+/// Move the split characters with moving effects.
+fn animate_glyph_offsets(
+    time: Res<Time>,
+    mut query: Query<(
+        &mut GlyphOffset,
+        &GlyphIndex,
+        Option<&WaveEffect>,
+        Option<&ShakeEffect>,
+    )>,
+) {
+    for (mut glyph_offset, glyph_index, wave, shake) in query.iter_mut() {
+        if wave.is_none() && shake.is_none() {
+            continue;
+        }
+
+        glyph_offset.0 = effect_offset(wave, shake, time.elapsed_secs(), glyph_index.0);
+    }
+}
+
+/// Offset the glyphs of text spans with moving effects from their laid out positions.
+pub(crate) fn animate_glyphs(
+    time: Res<Time>,
+    ui_scale: Res<UiScale>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut text_query: Query<(&mut AnimatedGlyphs, &ComputedTextBlock, &mut TextLayoutInfo)>,
+    effect_query: Query<(
+        Option<&GlyphOffset>,
+        Option<&WaveEffect>,
+        Option<&ShakeEffect>,
+    )>,
+) {
+    // The glyphs are positioned in physical pixels
+    let scale_factor = window_query
+        .get_single()
+        .map_or(1., |window| window.scale_factor())
+        * ui_scale.0;
+    let elapsed = time.elapsed_secs();
+
+    for (mut animated, text_block, mut layout) in text_query.iter_mut() {
+        // Our own modifications bypass change detection,
+        // so a change means that the text has been laid out again
+        if layout.is_changed() || animated.base_positions.len() != layout.glyphs.len() {
+            animated.base_positions = layout.glyphs.iter().map(|glyph| glyph.position).collect();
+        }
+
+        let layout = layout.bypass_change_detection();
+
+        for (index, (glyph, base_position)) in layout
+            .glyphs
+            .iter_mut()
+            .zip(&animated.base_positions)
+            .enumerate()
+        {
+            let Some((glyph_offset, wave, shake)) = text_block
+                .entities()
+                .get(glyph.span_index)
+                .and_then(|text_entity| effect_query.get(text_entity.entity).ok())
+            else {
+                continue;
+            };
+
+            // Split characters are moved as a whole, otherwise each glyph is moved individually
+            let offset = match glyph_offset {
+                Some(glyph_offset) => glyph_offset.0,
+                None => effect_offset(wave, shake, elapsed, index),
+            };
+
+            glyph.position = *base_position + offset * scale_factor;
+        }
+    }
+}
+
+/// The offset of the character with the given index caused by the moving effects, in logical pixels.
+fn effect_offset(
+    wave: Option<&WaveEffect>,
+    shake: Option<&ShakeEffect>,
+    elapsed: f32,
+    index: usize,
+) -> Vec2 {
+    let mut offset = Vec2::ZERO;
+
+    if let Some(wave) = wave {
+        offset.y += wave.amplitude * (elapsed * wave.frequency * TAU - index as f32 * 0.5).sin();
+    }
+
+    if let Some(shake) = shake {
+        let step = (elapsed * shake.rate) as u32;
+        offset += shake.strength
+            * Vec2::new(
+                pseudo_random(index as u32, step, 0),
+                pseudo_random(index as u32, step, 1),
+            );
+    }
+
+    offset
+}
+
+/// A cheap deterministic pseudo-random number in the range `[-1, 1]`.
+fn pseudo_random(index: u32, step: u32, axis: u32) -> f32 {
+    let mut hash = index.wrapping_mul(0x9E37_79B9)
+        ^ step.wrapping_mul(0x85EB_CA6B)
+        ^ axis.wrapping_mul(0xC2B2_AE35);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7FEB_352D);
+    hash ^= hash >> 15;
+
+    (hash as f32 / u32::MAX as f32) * 2. - 1.
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{asset::AssetPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::{Bbcode, BbcodePlugin};
+
+    #[test]
+    fn pulse_multiplies_alpha_of_text_and_layers() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<UiScale>()
+            .add_plugins(BbcodePlugin::new())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                250,
+            )));
+
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[outline][fade=0.5][pulse min=0.5]x[/pulse][/fade][/outline]",
+            ))
+            .id();
+
+        // The alpha values of the text and of one of the outline copies
+        let alphas = |app: &App| -> (f32, f32) {
+            let world = app.world();
+            let wrapper = world.get::<Children>(entity).unwrap()[0];
+            let layers = world.get::<Children>(wrapper).unwrap();
+            let alpha = |text: Entity| {
+                let span = world.get::<Children>(text).unwrap()[0];
+                world.get::<TextColor>(span).unwrap().0.alpha()
+            };
+            (alpha(layers[layers.len() - 1]), alpha(layers[0]))
+        };
+        let assert_alphas = |app: &App, expected: (f32, f32)| {
+            let (text, outline) = alphas(app);
+            assert!((text - expected.0).abs() < 1e-4, "{text} != {}", expected.0);
+            assert!(
+                (outline - expected.1).abs() < 1e-4,
+                "{outline} != {}",
+                expected.1
+            );
+        };
+
+        // The first frame has no time delta, the pulse starts fully visible
+        app.update();
+        assert_alphas(&app, (0.5, 1.));
+
+        app.update();
+        assert_alphas(&app, (0.375, 0.75));
+
+        app.update();
+        assert_alphas(&app, (0.25, 0.5));
+
+        app.update();
+        assert_alphas(&app, (0.375, 0.75));
+    }
+}
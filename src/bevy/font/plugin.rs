@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 use super::registry::FontRegistry;
+use crate::BbcodeSet;
 
 #[derive(Debug)]
 pub struct FontPlugin;
@@ -8,7 +9,7 @@ pub struct FontPlugin;
 impl Plugin for FontPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<FontRegistry>()
-            .add_systems(Update, update_font_registry);
+            .add_systems(Update, update_font_registry.in_set(BbcodeSet::FontRegistry));
     }
 }
 
@@ -0,0 +1,257 @@
+use bevy::{
+    prelude::*,
+    text::{ComputedTextBlock, PositionedGlyph, TextLayoutInfo},
+    ui::UiSystem,
+};
+
+use super::{effect::animate_glyphs, plugin::BbcodeSet};
+
+pub struct HighlightPlugin;
+
+impl Plugin for HighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_highlight_colors.in_set(BbcodeSet::Effects))
+            // The highlights are computed from the base glyph positions, before they are moved by effects
+            .add_systems(
+                PostUpdate,
+                update_highlights
+                    .after(UiSystem::PostLayout)
+                    .before(animate_glyphs),
+            );
+    }
+}
+
+/// The background color drawn behind a text span.
+///
+/// Inserted on the `TextSpan`s inside of a `[bg]` or `[highlight]` tag.
+/// Changing it updates the color of the drawn background.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Deref, DerefMut)]
+pub struct TextBackground(pub Color);
+
+/// Tracks the background rectangles drawn behind the spans of a text.
+///
+/// Inserted on text entities with spans that have a [`TextBackground`].
+/// The rectangles are spawned as siblings before the text, so they are rendered below it.
+#[derive(Debug, Clone, Default, Component)]
+pub(crate) struct TextHighlights {
+    nodes: Vec<Entity>,
+}
+
+/// A background rectangle drawn behind (a line of) a text span.
+#[derive(Debug, Component)]
+struct Highlight {
+    /// The span the background belongs to.
+    span: Entity,
+}
+
+/// Draw the backgrounds of the highlighted spans, whenever the text has been laid out again.
+///
+/// The existing background nodes are moved instead of respawned, because new nodes are only laid out in the next frame.
+fn update_highlights(
+    mut commands: Commands,
+    mut text_query: Query<(
+        &Parent,
+        &ComputedNode,
+        &ComputedTextBlock,
+        Ref<TextLayoutInfo>,
+        &mut TextHighlights,
+    )>,
+    background_query: Query<&TextBackground>,
+    mut highlight_query: Query<(&mut Highlight, &mut Node, &mut BackgroundColor)>,
+) {
+    for (parent, node, text_block, layout, mut highlights) in text_query.iter_mut() {
+        if !layout.is_changed() {
+            continue;
+        }
+
+        // The glyphs are positioned in physical pixels
+        let scale = node.inverse_scale_factor();
+        let mut runs = Vec::new();
+
+        for line in split_lines(&layout.glyphs) {
+            // Use the height of the whole line, so the backgrounds don't depend on the glyph shapes
+            let (top, bottom) = line
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(top, bottom), glyph| {
+                    let rect = glyph_rect(glyph);
+                    (top.min(rect.min.y), bottom.max(rect.max.y))
+                });
+
+            for run in line.chunk_by(|a, b| a.span_index == b.span_index) {
+                let Some(span) = text_block.entities().get(run[0].span_index) else {
+                    continue;
+                };
+                let Ok(background) = background_query.get(span.entity) else {
+                    continue;
+                };
+
+                let left = glyph_rect(&run[0]).min.x;
+                let right = glyph_rect(&run[run.len() - 1]).max.x;
+                let rect = Rect::new(left, top, right, bottom);
+
+                runs.push((span.entity, rect, background.0));
+            }
+        }
+
+        // Nodes despawned by someone else are replaced
+        highlights
+            .nodes
+            .retain(|&node| highlight_query.contains(node));
+        let mut new_nodes = Vec::new();
+
+        for (index, (span, rect, color)) in runs.iter().copied().enumerate() {
+            let (left, top) = (Val::Px(rect.min.x * scale), Val::Px(rect.min.y * scale));
+            let (width, height) = (
+                Val::Px(rect.width() * scale),
+                Val::Px(rect.height() * scale),
+            );
+
+            let existing = highlights
+                .nodes
+                .get(index)
+                .and_then(|&node| highlight_query.get_mut(node).ok());
+
+            if let Some((mut highlight, mut node, mut background_color)) = existing {
+                highlight.span = span;
+                background_color.set_if_neq(BackgroundColor(color));
+
+                // Only trigger a relayout if the background moved
+                if (node.left, node.top, node.width, node.height) != (left, top, width, height) {
+                    node.left = left;
+                    node.top = top;
+                    node.width = width;
+                    node.height = height;
+                }
+                continue;
+            }
+
+            let highlight = commands
+                .spawn((
+                    Highlight { span },
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left,
+                        top,
+                        width,
+                        height,
+                        ..default()
+                    },
+                    BackgroundColor(color),
+                ))
+                .id();
+
+            new_nodes.push(highlight);
+        }
+
+        // Also removes the nodes from the children of the parent
+        let run_count = runs.len().min(highlights.nodes.len());
+        for node in highlights.nodes.drain(run_count..) {
+            commands.entity(node).despawn_recursive();
+        }
+
+        if !new_nodes.is_empty() {
+            commands.entity(parent.get()).insert_children(0, &new_nodes);
+            highlights.nodes.extend(new_nodes);
+        }
+    }
+}
+
+/// Split the glyphs into lines, detected by the glyphs moving back to the left.
+fn split_lines(glyphs: &[PositionedGlyph]) -> impl Iterator<Item = &[PositionedGlyph]> {
+    glyphs.chunk_by(|a, b| b.position.x > a.position.x)
+}
+
+/// The rectangle covered by the glyph, in physical pixels.
+fn glyph_rect(glyph: &PositionedGlyph) -> Rect {
+    Rect::from_center_size(glyph.position, glyph.size)
+}
+
+/// Keep the colors of the drawn backgrounds in sync with their spans.
+fn update_highlight_colors(
+    mut highlight_query: Query<(&Highlight, &mut BackgroundColor)>,
+    background_query: Query<&TextBackground, Changed<TextBackground>>,
+) {
+    for (highlight, mut background_color) in highlight_query.iter_mut() {
+        if let Ok(background) = background_query.get(highlight.span) {
+            background_color.0 = background.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::{bevy::test_utils::lay_out_glyphs, Bbcode, BbcodePlugin};
+
+    #[test]
+    fn highlights_are_moved_on_relayout() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<UiScale>()
+            .add_plugins(BbcodePlugin::new());
+
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new("[bg=#ff0000]ab[/bg] c"))
+            .id();
+
+        app.update();
+
+        let wrapper = app.world().get::<Children>(entity).unwrap()[0];
+        let text = app.world().get::<Children>(wrapper).unwrap()[0];
+        let highlights = |app: &App| -> Vec<(Entity, Val, Val)> {
+            let world = app.world();
+            world
+                .get::<Children>(wrapper)
+                .unwrap()
+                .iter()
+                .filter_map(|&child| {
+                    world.get::<Highlight>(child)?;
+                    let node = world.get::<Node>(child).unwrap();
+                    Some((child, node.left, node.width))
+                })
+                .collect()
+        };
+
+        let glyphs = [
+            (Vec2::new(5., 5.), 1),
+            (Vec2::new(15., 5.), 1),
+            (Vec2::new(25., 5.), 2),
+        ];
+        lay_out_glyphs(app.world_mut(), text, &glyphs);
+        app.update();
+
+        let first = highlights(&app);
+        assert_eq!(first.len(), 1);
+        assert_eq!((first[0].1, first[0].2), (Val::Px(0.), Val::Px(20.)));
+
+        // The existing node is moved instead of spawning a new one
+        let glyphs = [
+            (Vec2::new(15., 5.), 1),
+            (Vec2::new(25., 5.), 1),
+            (Vec2::new(35., 5.), 2),
+        ];
+        lay_out_glyphs(app.world_mut(), text, &glyphs);
+        app.update();
+
+        assert_eq!(highlights(&app), [(first[0].0, Val::Px(10.), Val::Px(20.))]);
+
+        // Each line gets its own background
+        let glyphs = [(Vec2::new(5., 5.), 1), (Vec2::new(5., 25.), 1)];
+        lay_out_glyphs(app.world_mut(), text, &glyphs);
+        app.update();
+
+        let lines = highlights(&app);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().any(|&(node, ..)| node == first[0].0));
+
+        lay_out_glyphs(app.world_mut(), text, &[]);
+        app.update();
+
+        assert!(highlights(&app).is_empty());
+        assert_eq!(app.world().get::<Children>(wrapper).unwrap().len(), 1);
+    }
+}
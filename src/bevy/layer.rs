@@ -0,0 +1,75 @@
+use bevy::{prelude::*, ui::UiSystem};
+
+use super::color::BbCodeColor;
+
+pub struct LayerPlugin;
+
+impl Plugin for LayerPlugin {
+    fn build(&self, app: &mut App) {
+        // Sync the text before it's laid out, so the layers never lag behind
+        app.add_systems(PostUpdate, sync_layer_spans.before(UiSystem::Prepare));
+    }
+}
+
+/// An outline around the text, drawn with copies of the text offset in all directions.
+///
+/// Set via the `[outline]` tag or as default via [`BbcodeSettings::with_outline`](crate::BbcodeSettings::with_outline).
+#[derive(Debug, Clone)]
+pub struct TextOutline {
+    /// The color of the outline, which can also be a named color.
+    pub color: BbCodeColor,
+    /// The width of the outline, in logical pixels.
+    pub width: f32,
+}
+
+impl Default for TextOutline {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK.into(),
+            width: 1.,
+        }
+    }
+}
+
+/// A drop shadow behind the text, drawn with a copy of the text.
+///
+/// Set via the `[shadow]` tag or as default via [`BbcodeSettings::with_shadow`](crate::BbcodeSettings::with_shadow).
+#[derive(Debug, Clone)]
+pub struct TextShadow {
+    /// The color of the shadow, which can also be a named color.
+    pub color: BbCodeColor,
+    /// The offset of the shadow from the text, in logical pixels.
+    pub offset: Vec2,
+}
+
+impl Default for TextShadow {
+    fn default() -> Self {
+        Self {
+            color: Color::srgba(0., 0., 0., 0.5).into(),
+            offset: Vec2::new(2., 2.),
+        }
+    }
+}
+
+/// A span of a copy of the text, used to draw outlines and shadows.
+///
+/// Mirrors the text of the original span, e.g. while the text is revealed.
+#[derive(Debug, Clone, Copy, Component)]
+pub(crate) struct LayerSpan {
+    /// The span that is copied.
+    pub(crate) source: Entity,
+}
+
+/// Copy the changed text of the original spans to their layers.
+fn sync_layer_spans(
+    mut layer_query: Query<(&LayerSpan, &mut TextSpan)>,
+    source_query: Query<&TextSpan, (Changed<TextSpan>, Without<LayerSpan>)>,
+) {
+    for (layer, mut text_span) in layer_query.iter_mut() {
+        if let Ok(source) = source_query.get(layer.source) {
+            if text_span.0 != source.0 {
+                text_span.0.clone_from(&source.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// Looks up translated BBCode fragments for the `[t]` tag.
+///
+/// Implemented for all functions with a matching signature.
+pub trait BbcodeTranslator: Send + Sync + 'static {
+    /// Get the translated BBCode fragment for the given key.
+    ///
+    /// The arguments are the key/value parameters of the tag, e.g. `count` -> `3` for `[t=items count=3]`.
+    /// They can be used to select plural forms or to be inserted into the translation.
+    fn translate(&self, key: &str, args: &HashMap<&str, &str>) -> Option<String>;
+}
+
+impl<F> BbcodeTranslator for F
+where
+    F: Fn(&str, &HashMap<&str, &str>) -> Option<String> + Send + Sync + 'static,
+{
+    fn translate(&self, key: &str, args: &HashMap<&str, &str>) -> Option<String> {
+        self(key, args)
+    }
+}
+
+/// The translator used to resolve the `[t]` tag.
+///
+/// All BBCode text is rebuilt when this resource is changed,
+/// e.g. via [`BbcodeTranslations::set_translator`] when the locale changes.
+#[derive(Resource)]
+pub struct BbcodeTranslations {
+    translator: Box<dyn BbcodeTranslator>,
+}
+
+impl BbcodeTranslations {
+    /// Use the given translator to resolve the `[t]` tag.
+    pub fn new<T: BbcodeTranslator>(translator: T) -> Self {
+        Self {
+            translator: Box::new(translator),
+        }
+    }
+
+    /// Replace the translator, e.g. to change the locale.
+    pub fn set_translator<T: BbcodeTranslator>(&mut self, translator: T) {
+        self.translator = Box::new(translator);
+    }
+
+    /// Get the translated BBCode fragment for the given key.
+    pub fn translate(&self, key: &str, args: &HashMap<&str, &str>) -> Option<String> {
+        self.translator.translate(key, args)
+    }
+}
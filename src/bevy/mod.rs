@@ -1,10 +1,32 @@
 pub(crate) mod bbcode;
 pub(crate) mod color;
 pub(crate) mod conversion;
+pub(crate) mod effect;
 pub(crate) mod font;
+pub(crate) mod highlight;
+pub(crate) mod layer;
+pub(crate) mod localization;
 pub(crate) mod plugin;
+pub(crate) mod reveal;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub(crate) mod theme;
+pub(crate) mod tooltip;
 
 pub use bbcode::{Bbcode, BbcodeSettings};
-pub use color::ColorMap;
+pub use color::{
+    BbCodeBackgroundColored, BbCodeGradientColored, BbcodeColorOverrides, ColorMap,
+    ColorMapChanged, ColorModifier, ColorPalette, ColorTransition,
+};
+#[cfg(any(feature = "ron", feature = "json"))]
+pub use color::{ColorMapAsset, ColorMapLoader, ColorMapLoaderError};
+pub use conversion::{BbcodeRebuilt, BbcodeSpans};
+pub use effect::{GlyphOffset, PulseEffect, RainbowEffect, ShakeEffect, WaveEffect};
 pub use font::*;
-pub use plugin::BbcodePlugin;
+pub use highlight::TextBackground;
+pub use layer::{TextOutline, TextShadow};
+pub use localization::{BbcodeTranslations, BbcodeTranslator};
+pub use plugin::{BbcodePlugin, BbcodeSet};
+pub use reveal::{BbcodeReveal, BbcodeRevealFinished};
+pub use theme::BbcodeTheme;
+pub use tooltip::{BbcodeTooltip, TooltipStyle};
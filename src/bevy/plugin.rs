@@ -3,12 +3,25 @@ use bevy::{
     prelude::*,
 };
 
-use super::{color::ColorPlugin, conversion::convert_bbcode, font::FontPlugin};
+use super::{
+    color::ColorPlugin,
+    conversion::{convert_bbcode, BbcodeRebuilt},
+    effect::EffectPlugin,
+    font::FontPlugin,
+    highlight::HighlightPlugin,
+    layer::LayerPlugin,
+    reveal::RevealPlugin,
+    tooltip::TooltipPlugin,
+};
 
 #[derive(Debug, Default)]
 pub struct BbcodePlugin {
     /// The path to a folder containing the fonts to use.
     font_folder_path: Option<AssetPath<'static>>,
+
+    /// The path to a file containing named colors.
+    #[cfg(any(feature = "ron", feature = "json"))]
+    color_map_path: Option<AssetPath<'static>>,
 }
 
 impl BbcodePlugin {
@@ -18,6 +31,8 @@ impl BbcodePlugin {
     pub fn new() -> Self {
         Self {
             font_folder_path: None,
+            #[cfg(any(feature = "ron", feature = "json"))]
+            color_map_path: None,
         }
     }
 
@@ -27,12 +42,61 @@ impl BbcodePlugin {
         self.font_folder_path = Some(folder_path.into());
         self
     }
+
+    /// Load the named colors and palettes of the given `.colors.ron` or `.colors.json` file into the [`ColorMap`](crate::ColorMap).
+    ///
+    /// Changes to the file are applied automatically when hot reloading is enabled.
+    #[cfg(any(feature = "ron", feature = "json"))]
+    pub fn with_color_map<P: Into<AssetPath<'static>>>(mut self, path: P) -> Self {
+        self.color_map_path = Some(path.into());
+        self
+    }
+}
+
+/// The system sets of the BBCode systems, which run in [`Update`] in the order of the variants.
+///
+/// The text is converted before it's laid out in [`PostUpdate`], so it's never shown unformatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum BbcodeSet {
+    /// Updates the registry of the loaded fonts.
+    FontRegistry,
+    /// Converts the changed [`Bbcode`](crate::Bbcode) entities into text spans.
+    ///
+    /// Spawn or change `Bbcode` entities before this set and query their spans after it to do both in the same frame.
+    Convert,
+    /// Sends the [`ColorMapChanged`](crate::ColorMapChanged) events and updates the colors of the text.
+    ///
+    /// Changes to the [`ColorMap`](crate::ColorMap) made before this set are applied in the same frame.
+    Colors,
+    /// Reveals the text, animates the text effects and shows the tooltips.
+    ///
+    /// Runs after the colors have been updated, so the effects can modify the text colors.
+    Effects,
 }
 
 impl Plugin for BbcodePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((FontPlugin, ColorPlugin))
-            .add_systems(Update, convert_bbcode);
+        app.add_plugins((
+            FontPlugin,
+            ColorPlugin,
+            TooltipPlugin,
+            RevealPlugin,
+            EffectPlugin,
+            HighlightPlugin,
+            LayerPlugin,
+        ))
+        .configure_sets(
+            Update,
+            (
+                BbcodeSet::FontRegistry,
+                BbcodeSet::Convert,
+                BbcodeSet::Colors,
+                BbcodeSet::Effects,
+            )
+                .chain(),
+        )
+        .add_event::<BbcodeRebuilt>()
+        .add_systems(Update, convert_bbcode.in_set(BbcodeSet::Convert));
 
         let asset_server = app.world().resource::<AssetServer>();
 
@@ -43,6 +107,16 @@ impl Plugin for BbcodePlugin {
             // We still need to store the handle to keep the fonts loaded
             app.insert_resource(FontFolder { _handle: handle });
         }
+
+        #[cfg(any(feature = "ron", feature = "json"))]
+        if let Some(path) = &self.color_map_path {
+            let handle = app
+                .world()
+                .resource::<AssetServer>()
+                .load::<super::ColorMapAsset>(path.clone());
+            // Keep the colors loaded to react to changes of the file
+            app.insert_resource(ColorMapFile { _handle: handle });
+        }
     }
 }
 
@@ -51,3 +125,10 @@ struct FontFolder {
     /// Keep the assets loaded by storing the strong handle
     _handle: Handle<LoadedFolder>,
 }
+
+#[cfg(any(feature = "ron", feature = "json"))]
+#[derive(Debug, Resource)]
+struct ColorMapFile {
+    /// Keep the asset loaded by storing the strong handle
+    _handle: Handle<super::ColorMapAsset>,
+}
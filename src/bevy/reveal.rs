@@ -0,0 +1,396 @@
+use bevy::prelude::*;
+
+use super::{layer::LayerSpan, plugin::BbcodeSet};
+
+pub struct RevealPlugin;
+
+impl Plugin for RevealPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BbcodeRevealFinished>()
+            .add_systems(Update, reveal_text.in_set(BbcodeSet::Effects));
+    }
+}
+
+/// Reveal the text of a [`Bbcode`](super::Bbcode) entity character by character, like a typewriter.
+///
+/// Works on the already converted text spans, so the formatting is kept intact.
+/// The pacing can be controlled inline via the `[pause=0.5]` and `[speed=2]` tags.
+///
+/// When the content is changed, the reveal starts from the beginning.
+#[derive(Debug, Clone, Component)]
+pub struct BbcodeReveal {
+    /// The number of characters to reveal per second.
+    pub chars_per_second: f32,
+    /// The additional time in seconds to wait after punctuation.
+    pub punctuation_pause: f32,
+
+    /// The captured text of the spans to reveal, in order.
+    spans: Vec<RevealSpan>,
+    /// The index of the span that is currently being revealed.
+    span_index: usize,
+    /// The byte index into the text of the span that is currently being revealed.
+    byte_index: usize,
+    /// Whether the pause of the current span has already been waited for.
+    pause_applied: bool,
+    /// The time in seconds until the next character is revealed.
+    cooldown: f32,
+    /// Whether the entire text should be revealed immediately.
+    skip: bool,
+    /// Whether the entire text has been revealed.
+    finished: bool,
+}
+
+impl BbcodeReveal {
+    /// Reveal the text with the given number of characters per second.
+    pub fn new(chars_per_second: f32) -> Self {
+        Self {
+            chars_per_second,
+            punctuation_pause: 0.,
+            spans: Vec::new(),
+            span_index: 0,
+            byte_index: 0,
+            pause_applied: false,
+            cooldown: 0.,
+            skip: false,
+            finished: false,
+        }
+    }
+
+    /// Wait for the given time in seconds after punctuation.
+    pub fn with_punctuation_pause(mut self, punctuation_pause: f32) -> Self {
+        self.punctuation_pause = punctuation_pause;
+        self
+    }
+
+    /// Reveal the remaining text immediately.
+    pub fn skip(&mut self) {
+        self.skip = true;
+    }
+
+    /// Start revealing the text from the beginning again.
+    pub fn restart(&mut self) {
+        self.span_index = 0;
+        self.byte_index = 0;
+        self.pause_applied = false;
+        self.cooldown = 0.;
+        self.skip = false;
+        self.finished = false;
+    }
+
+    /// Determine if the entire text has been revealed.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RevealSpan {
+    /// The span entity.
+    entity: Entity,
+    /// The full text of the span.
+    text: String,
+    /// The time in seconds to wait before revealing the span.
+    pause: f32,
+    /// The multiplier of the reveal speed.
+    speed: f32,
+}
+
+/// Wait for the given time in seconds before revealing the following text.
+///
+/// Inserted on the empty `TextSpan` created by the `[pause]` tag.
+#[derive(Debug, Clone, Copy, Component)]
+pub(crate) struct RevealPause(pub(crate) f32);
+
+/// Multiply the reveal speed of the text span.
+///
+/// Inserted on the `TextSpan`s inside of a `[speed]` tag.
+#[derive(Debug, Clone, Copy, Component)]
+pub(crate) struct RevealSpeed(pub(crate) f32);
+
+/// Sent when a [`BbcodeReveal`] has revealed the entire text.
+#[derive(Debug, Clone, Event)]
+pub struct BbcodeRevealFinished {
+    /// The entity with the [`BbcodeReveal`] component.
+    pub entity: Entity,
+}
+
+/// The text spans to reveal.
+///
+/// The copies of the text for outlines and shadows are skipped, they mirror the original spans.
+type SpanQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut TextSpan,
+        Option<&'static RevealPause>,
+        Option<&'static RevealSpeed>,
+    ),
+    Without<LayerSpan>,
+>;
+
+/// Reveal the next characters of all text with a [`BbcodeReveal`] component.
+fn reveal_text(
+    time: Res<Time>,
+    mut reveal_query: Query<(Entity, &mut BbcodeReveal, Option<Ref<Children>>)>,
+    children_query: Query<&Children>,
+    mut span_query: SpanQuery,
+    mut finished_events: EventWriter<BbcodeRevealFinished>,
+) {
+    for (entity, mut reveal, children) in reveal_query.iter_mut() {
+        let is_rebuilt = children
+            .as_ref()
+            .is_some_and(|children| children.is_changed());
+
+        let is_restarted = reveal.is_added() || is_rebuilt;
+
+        if is_restarted {
+            // Capture the full text of the spans before hiding it
+            let mut spans = Vec::new();
+            collect_spans(entity, &children_query, &span_query, &mut spans);
+            reveal.spans = spans;
+            reveal.restart();
+        }
+
+        if reveal.finished {
+            continue;
+        }
+
+        if reveal.skip || reveal.chars_per_second <= 0. {
+            reveal.span_index = reveal.spans.len();
+        } else {
+            // The time before the text has been (re)built doesn't count
+            if !is_restarted {
+                reveal.cooldown -= time.delta_secs();
+            }
+            advance(&mut reveal);
+        }
+
+        // Show the revealed part of each span
+        let reveal = reveal.as_mut();
+        for (index, span) in reveal.spans.iter().enumerate() {
+            let Ok((mut text_span, _, _)) = span_query.get_mut(span.entity) else {
+                continue;
+            };
+
+            let visible_len = match index.cmp(&reveal.span_index) {
+                std::cmp::Ordering::Less => span.text.len(),
+                std::cmp::Ordering::Equal => reveal.byte_index,
+                std::cmp::Ordering::Greater => 0,
+            };
+
+            if text_span.0.len() != visible_len {
+                text_span.0 = span.text[..visible_len].to_owned();
+            }
+        }
+
+        if reveal.span_index >= reveal.spans.len() {
+            reveal.finished = true;
+            finished_events.send(BbcodeRevealFinished { entity });
+        }
+    }
+}
+
+/// Reveal characters until the cooldown is used up.
+fn advance(reveal: &mut BbcodeReveal) {
+    while reveal.cooldown <= 0. {
+        let Some(span) = reveal.spans.get(reveal.span_index) else {
+            return;
+        };
+
+        if !reveal.pause_applied {
+            reveal.pause_applied = true;
+            reveal.cooldown += span.pause;
+            continue;
+        }
+
+        match span.text[reveal.byte_index..].chars().next() {
+            Some(next_char) => {
+                reveal.byte_index += next_char.len_utf8();
+                reveal.cooldown += 1. / (reveal.chars_per_second * span.speed);
+
+                if matches!(next_char, '.' | ',' | '!' | '?' | ';' | ':') {
+                    reveal.cooldown += reveal.punctuation_pause;
+                }
+            }
+            None => {
+                reveal.span_index += 1;
+                reveal.byte_index = 0;
+                reveal.pause_applied = false;
+            }
+        }
+    }
+}
+
+/// Collect the text spans below the given entity in order.
+fn collect_spans(
+    entity: Entity,
+    children_query: &Query<&Children>,
+    span_query: &SpanQuery,
+    spans: &mut Vec<RevealSpan>,
+) {
+    let Ok(children) = children_query.get(entity) else {
+        return;
+    };
+
+    for &child in children {
+        if let Ok((text_span, pause, speed)) = span_query.get(child) {
+            spans.push(RevealSpan {
+                entity: child,
+                text: text_span.0.clone(),
+                pause: pause.map_or(0., |pause| pause.0),
+                speed: speed.map_or(1., |speed| speed.0),
+            });
+        }
+
+        collect_spans(child, children_query, span_query, spans);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{asset::AssetPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::{Bbcode, BbcodePlugin, BbcodeSet};
+
+    /// A reveal of the given spans, with their pause and speed.
+    fn reveal_spans(chars_per_second: f32, spans: &[(&str, f32, f32)]) -> BbcodeReveal {
+        let mut reveal = BbcodeReveal::new(chars_per_second);
+        reveal.spans = spans
+            .iter()
+            .map(|&(text, pause, speed)| RevealSpan {
+                entity: Entity::PLACEHOLDER,
+                text: text.to_string(),
+                pause,
+                speed,
+            })
+            .collect();
+        reveal
+    }
+
+    /// Advance the reveal by the given time in seconds and return the revealed text.
+    fn step(reveal: &mut BbcodeReveal, delta: f32) -> String {
+        reveal.cooldown -= delta;
+        advance(reveal);
+
+        let mut text: String = reveal
+            .spans
+            .iter()
+            .take(reveal.span_index)
+            .map(|span| span.text.as_str())
+            .collect();
+        if let Some(span) = reveal.spans.get(reveal.span_index) {
+            text.push_str(&span.text[..reveal.byte_index]);
+        }
+        text
+    }
+
+    #[test]
+    fn reveals_chars_per_second() {
+        let mut reveal = reveal_spans(4., &[("abc", 0., 1.), ("déf", 0., 1.)]);
+
+        // The first character is shown immediately
+        assert_eq!(step(&mut reveal, 0.), "a");
+        assert_eq!(step(&mut reveal, 0.125), "a");
+        assert_eq!(step(&mut reveal, 0.125), "ab");
+        // Multiple characters are revealed in long frames, also across spans
+        assert_eq!(step(&mut reveal, 0.5), "abcd");
+        assert_eq!(step(&mut reveal, 0.25), "abcdé");
+        assert_eq!(step(&mut reveal, 1.), "abcdéf");
+    }
+
+    #[test]
+    fn pause_and_speed_change_pacing() {
+        let mut reveal = reveal_spans(4., &[("ab", 0., 1.), ("", 1., 1.), ("cde", 0., 2.)])
+            .with_punctuation_pause(0.5);
+
+        assert_eq!(step(&mut reveal, 0.), "a");
+        assert_eq!(step(&mut reveal, 0.25), "ab");
+        // Wait for the pause before revealing the next span
+        assert_eq!(step(&mut reveal, 0.25), "ab");
+        assert_eq!(step(&mut reveal, 0.75), "ab");
+        assert_eq!(step(&mut reveal, 0.25), "abc");
+        // The span is revealed twice as fast
+        assert_eq!(step(&mut reveal, 0.125), "abcd");
+        assert_eq!(step(&mut reveal, 0.125), "abcde");
+
+        let mut reveal = reveal_spans(4., &[("a.b", 0., 1.)]).with_punctuation_pause(0.5);
+
+        assert_eq!(step(&mut reveal, 0.), "a");
+        assert_eq!(step(&mut reveal, 0.25), "a.");
+        assert_eq!(step(&mut reveal, 0.5), "a.");
+        assert_eq!(step(&mut reveal, 0.25), "a.b");
+    }
+
+    #[test]
+    fn reveal_restarts_on_rebuild_and_finishes_once() {
+        #[derive(Default, Resource)]
+        struct Finished(Vec<Entity>);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<UiScale>()
+            .add_plugins(BbcodePlugin::new())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                125,
+            )))
+            .init_resource::<Finished>()
+            .add_systems(
+                Update,
+                (|mut events: EventReader<BbcodeRevealFinished>,
+                  mut finished: ResMut<Finished>| {
+                    finished.0.extend(events.read().map(|event| event.entity));
+                })
+                .after(BbcodeSet::Effects),
+            );
+
+        let entity = app
+            .world_mut()
+            .spawn((Bbcode::new("ab [b]cd[/b]"), BbcodeReveal::new(8.)))
+            .id();
+
+        let texts = |app: &App| -> Vec<String> {
+            let world = app.world();
+            world
+                .get::<Children>(entity)
+                .unwrap()
+                .iter()
+                .map(|&span| world.get::<TextSpan>(span).unwrap().0.clone())
+                .collect()
+        };
+
+        // The first frame has no time delta
+        app.update();
+        assert_eq!(texts(&app), ["a", ""]);
+
+        app.update();
+        app.update();
+        app.update();
+        assert_eq!(texts(&app), ["ab ", "c"]);
+
+        // The reveal starts again for the new text
+        app.world_mut().get_mut::<Bbcode>(entity).unwrap().content = "xyz".into();
+        app.update();
+        assert_eq!(texts(&app), ["x"]);
+        assert!(app.world().resource::<Finished>().0.is_empty());
+
+        app.world_mut()
+            .get_mut::<BbcodeReveal>(entity)
+            .unwrap()
+            .skip();
+        app.update();
+        assert_eq!(texts(&app), ["xyz"]);
+        assert!(app
+            .world()
+            .get::<BbcodeReveal>(entity)
+            .unwrap()
+            .is_finished());
+
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<Finished>().0, [entity]);
+    }
+}
@@ -0,0 +1,62 @@
+//! Helpers to simulate the text layout in tests, which is only computed with a renderer.
+
+use bevy::{
+    prelude::*,
+    text::{
+        ComputedTextBlock, CosmicFontSystem, GlyphAtlasInfo, GlyphAtlasLocation, PositionedGlyph,
+        TextBounds, TextLayoutInfo, TextPipeline,
+    },
+};
+
+/// Lay out the given glyphs for the text entity, each with its position and the index of its span.
+///
+/// The span index `0` is the text entity itself, followed by its child spans.
+pub(crate) fn lay_out_glyphs(world: &mut World, text: Entity, glyphs: &[(Vec2, usize)]) {
+    world.init_resource::<TextPipeline>();
+    world.init_resource::<CosmicFontSystem>();
+
+    let entities: Vec<_> = std::iter::once(text)
+        .chain(world.get::<Children>(text).unwrap().iter().copied())
+        .collect();
+    let text_font = TextFont::default();
+
+    world.resource_scope(|world, mut pipeline: Mut<TextPipeline>| {
+        world.resource_scope(|world, mut font_system: Mut<CosmicFontSystem>| {
+            world.resource_scope(|world, fonts: Mut<Assets<Font>>| {
+                let mut text_block = world.get_mut::<ComputedTextBlock>(text).unwrap();
+                // The spans are recorded without their text, which would need loaded fonts
+                let _ = pipeline.update_buffer(
+                    &fonts,
+                    entities.iter().enumerate().map(|(index, &entity)| {
+                        (entity, index.min(1), "", &text_font, Color::WHITE)
+                    }),
+                    LineBreak::default(),
+                    JustifyText::default(),
+                    TextBounds::UNBOUNDED,
+                    1.,
+                    &mut text_block,
+                    &mut font_system,
+                );
+            });
+        });
+    });
+
+    world.get_mut::<TextLayoutInfo>(text).unwrap().glyphs = glyphs
+        .iter()
+        .map(|&(position, span_index)| {
+            PositionedGlyph::new(
+                position,
+                Vec2::splat(10.),
+                GlyphAtlasInfo {
+                    texture: default(),
+                    texture_atlas: default(),
+                    location: GlyphAtlasLocation {
+                        glyph_index: 0,
+                        offset: IVec2::ZERO,
+                    },
+                },
+                span_index,
+            )
+        })
+        .collect();
+}
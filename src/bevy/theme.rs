@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+
+/// The styling of block-level tags, like headings, horizontal rules, quotes, lists, tables and code.
+#[derive(Debug, Clone)]
+pub struct BbcodeTheme {
+    /// The font sizes of the `[h1]`, `[h2]` and `[h3]` headings, relative to the default font size.
+    pub heading_scales: [f32; 3],
+    /// The vertical space between blocks, in logical pixels.
+    pub block_spacing: f32,
+
+    /// The color of horizontal `[hr]` rules.
+    pub rule_color: Color,
+    /// The thickness of horizontal `[hr]` rules, in logical pixels.
+    pub rule_thickness: f32,
+
+    /// The indentation of `[quote]` blocks, in logical pixels.
+    pub quote_indent: f32,
+    /// The color of the border to the left of `[quote]` blocks.
+    pub quote_border_color: Color,
+    /// The width of the border to the left of `[quote]` blocks, in logical pixels.
+    pub quote_border_width: f32,
+
+    /// The bullets of unordered `[list]`s, cycled through by nesting level.
+    pub list_bullets: Vec<String>,
+    /// The text after the numbers of ordered lists, e.g. `.` for `1.`.
+    pub list_number_suffix: String,
+    /// The width of the bullets and numbers in front of list items, in logical pixels.
+    ///
+    /// Wrapped lines of an item are indented by the same amount.
+    pub list_indent: f32,
+
+    /// The width of the borders of `[table]`s and their cells, in logical pixels.
+    ///
+    /// Can be overwritten per table via `[table border=1]`.
+    pub table_border_width: f32,
+    /// The color of the borders of `[table]`s and their cells.
+    pub table_border_color: Color,
+    /// The space between the borders and the content of table cells, in logical pixels.
+    ///
+    /// Can be overwritten per table via `[table padding=4]`.
+    pub table_cell_padding: f32,
+
+    /// The background color of `[code]` blocks.
+    pub code_background_color: Color,
+    /// The space between the background and the text of `[code]` blocks, in logical pixels.
+    pub code_padding: f32,
+}
+
+impl BbcodeTheme {
+    /// The font size scale of the heading with the given level (1 to 3).
+    pub fn heading_scale(&self, level: u8) -> f32 {
+        self.heading_scales[(level.clamp(1, 3) - 1) as usize]
+    }
+
+    /// The bullet of unordered lists at the given nesting level, starting at 0.
+    pub fn list_bullet(&self, depth: usize) -> &str {
+        if self.list_bullets.is_empty() {
+            return "";
+        }
+
+        &self.list_bullets[depth % self.list_bullets.len()]
+    }
+}
+
+impl Default for BbcodeTheme {
+    fn default() -> Self {
+        Self {
+            heading_scales: [2., 1.5, 1.25],
+            block_spacing: 8.,
+            rule_color: Color::srgb(0.5, 0.5, 0.5),
+            rule_thickness: 2.,
+            quote_indent: 12.,
+            quote_border_color: Color::srgb(0.5, 0.5, 0.5),
+            quote_border_width: 3.,
+            list_bullets: vec!["•".to_string(), "◦".to_string(), "▪".to_string()],
+            list_number_suffix: ".".to_string(),
+            list_indent: 24.,
+            table_border_width: 0.,
+            table_border_color: Color::srgb(0.5, 0.5, 0.5),
+            table_cell_padding: 4.,
+            code_background_color: Color::srgba(0., 0., 0., 0.3),
+            code_padding: 8.,
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use bevy::{
+    prelude::*,
+    text::{ComputedTextBlock, TextLayoutInfo},
+    ui::RelativeCursorPosition,
+    window::PrimaryWindow,
+};
+
+use super::{
+    bbcode::{Bbcode, BbcodeSettings},
+    plugin::BbcodeSet,
+};
+
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TooltipStyle>()
+            .add_systems(Update, update_tooltips.in_set(BbcodeSet::Effects));
+    }
+}
+
+/// The BBCode content of a tooltip, shown when hovering the text span it's attached to.
+///
+/// Inserted on all `TextSpan`s inside of a `[tooltip]` tag.
+#[derive(Debug, Clone, Component)]
+pub struct BbcodeTooltip {
+    /// The bbcode-formatted content of the tooltip.
+    pub content: String,
+}
+
+/// The style of the tooltip popups.
+#[derive(Debug, Clone, Resource)]
+pub struct TooltipStyle {
+    /// The background color of the popup.
+    pub background_color: Color,
+    /// The padding between the border of the popup and its text.
+    pub padding: UiRect,
+    /// The offset of the popup from the cursor position, in logical pixels.
+    pub cursor_offset: Vec2,
+}
+
+impl Default for TooltipStyle {
+    fn default() -> Self {
+        Self {
+            background_color: Color::srgba(0.1, 0.1, 0.1, 0.9),
+            padding: UiRect::all(Val::Px(6.)),
+            cursor_offset: Vec2::new(12., 12.),
+        }
+    }
+}
+
+/// The popup currently displaying a tooltip.
+#[derive(Debug, Component)]
+struct TooltipPopup {
+    /// The bbcode content of the shown tooltip.
+    ///
+    /// Text with a tooltip can be split into multiple spans, e.g. one per character,
+    /// so the popup is kept while hovering any span with the same content.
+    content: String,
+}
+
+/// Show the tooltip of the hovered text span and remove tooltips which are no longer hovered.
+fn update_tooltips(
+    mut commands: Commands,
+    style: Res<TooltipStyle>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    text_query: Query<(
+        Entity,
+        &RelativeCursorPosition,
+        &ComputedNode,
+        &ComputedTextBlock,
+        &TextLayoutInfo,
+    )>,
+    ancestor_query: Query<(Option<&BbcodeSettings>, Option<&Parent>)>,
+    tooltip_query: Query<&BbcodeTooltip>,
+    mut popup_query: Query<(Entity, &TooltipPopup, &mut Node)>,
+) {
+    let cursor_position = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    let hovered = cursor_position.and_then(|cursor_position| {
+        text_query
+            .iter()
+            .find_map(|(entity, relative_cursor, node, text_block, layout)| {
+                let position = relative_cursor.normalized? * node.size();
+
+                // Find the glyph under the cursor and look up the span it belongs to
+                let glyph = layout.glyphs.iter().find(|glyph| {
+                    Rect::from_center_size(glyph.position, glyph.size).contains(position)
+                })?;
+                let span = text_block.entities().get(glyph.span_index)?.entity;
+                let tooltip = tooltip_query.get(span).ok()?;
+
+                let settings = bbcode_settings(entity, &ancestor_query)?;
+
+                Some((tooltip, settings, cursor_position))
+            })
+    });
+
+    let mut is_shown = false;
+
+    for (popup_entity, popup, mut node) in popup_query.iter_mut() {
+        match hovered {
+            Some((tooltip, _, cursor_position)) if tooltip.content == popup.content => {
+                // Let the popup follow the cursor
+                let position = cursor_position + style.cursor_offset;
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+                is_shown = true;
+            }
+            _ => commands.entity(popup_entity).despawn_recursive(),
+        }
+    }
+
+    if is_shown {
+        return;
+    }
+
+    if let Some((tooltip, settings, cursor_position)) = hovered {
+        let position = cursor_position + style.cursor_offset;
+
+        commands.spawn((
+            TooltipPopup {
+                content: tooltip.content.clone(),
+            },
+            Bbcode::new(tooltip.content.clone()),
+            settings.clone(),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(position.x),
+                top: Val::Px(position.y),
+                padding: style.padding,
+                ..default()
+            },
+            BackgroundColor(style.background_color),
+            GlobalZIndex(i32::MAX),
+        ));
+    }
+}
+
+/// The settings of the `Bbcode` entity the text belongs to.
+///
+/// Blocks of text are spawned as descendants of the `Bbcode` entity, so the settings are looked up in the ancestors.
+fn bbcode_settings<'a>(
+    mut entity: Entity,
+    ancestor_query: &'a Query<(Option<&BbcodeSettings>, Option<&Parent>)>,
+) -> Option<&'a BbcodeSettings> {
+    loop {
+        match ancestor_query.get(entity).ok()? {
+            (Some(settings), _) => return Some(settings),
+            (None, Some(parent)) => entity = parent.get(),
+            (None, None) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+
+    use super::*;
+    use crate::{bevy::test_utils::lay_out_glyphs, BbcodePlugin};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<UiScale>()
+            .add_plugins(BbcodePlugin::new());
+
+        let mut window = Window::default();
+        window.set_cursor_position(Some(Vec2::new(100., 50.)));
+        app.world_mut().spawn((window, PrimaryWindow));
+
+        app
+    }
+
+    /// Move the cursor over the span with the given index of the text entity, or away from the text.
+    fn hover_span(app: &mut App, text: Entity, span_index: Option<usize>) {
+        let world = app.world_mut();
+        let glyphs: Vec<_> = span_index
+            .map(|span_index| (Vec2::ZERO, span_index))
+            .into_iter()
+            .collect();
+        lay_out_glyphs(world, text, &glyphs);
+
+        world
+            .get_mut::<RelativeCursorPosition>(text)
+            .unwrap()
+            .normalized = span_index.map(|_| Vec2::ZERO);
+    }
+
+    /// The content and settings of the shown tooltip popups.
+    fn popups(app: &mut App) -> Vec<(String, f32)> {
+        app.world_mut()
+            .query_filtered::<(&Bbcode, &BbcodeSettings), With<TooltipPopup>>()
+            .iter(app.world())
+            .map(|(bbcode, settings)| (bbcode.content.clone(), settings.font_size))
+            .collect()
+    }
+
+    #[test]
+    fn popup_is_shown_while_hovering_span() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "plain [tooltip=\"Deals [c=red]5[/c] damage\"]Fireball[/tooltip]",
+            ))
+            .id();
+
+        app.update();
+
+        // Spans without tooltips don't show a popup
+        hover_span(&mut app, entity, Some(1));
+        app.update();
+        assert!(popups(&mut app).is_empty());
+
+        hover_span(&mut app, entity, Some(2));
+        app.update();
+        assert_eq!(
+            popups(&mut app),
+            [("Deals [c=red]5[/c] damage".to_string(), 20.)]
+        );
+
+        // The popup is kept while the span is hovered
+        app.update();
+        assert_eq!(popups(&mut app).len(), 1);
+
+        hover_span(&mut app, entity, None);
+        app.update();
+        assert!(popups(&mut app).is_empty());
+    }
+
+    #[test]
+    fn popup_is_kept_across_split_spans() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(Bbcode::new(
+                "[tooltip=first][gradient]ab[/gradient][/tooltip][tooltip=second]c[/tooltip]",
+            ))
+            .id();
+
+        app.update();
+
+        let popup_entities = |app: &mut App| -> Vec<Entity> {
+            app.world_mut()
+                .query_filtered::<Entity, With<TooltipPopup>>()
+                .iter(app.world())
+                .collect()
+        };
+
+        hover_span(&mut app, entity, Some(1));
+        app.update();
+        let popup = popup_entities(&mut app);
+        assert_eq!(popup.len(), 1);
+
+        // The gradient splits the text into characters, which share the tooltip
+        hover_span(&mut app, entity, Some(2));
+        app.update();
+        assert_eq!(popup_entities(&mut app), popup);
+
+        hover_span(&mut app, entity, Some(3));
+        app.update();
+        assert_eq!(popups(&mut app), [("second".to_string(), 20.)]);
+    }
+
+    #[test]
+    fn tooltips_work_inside_of_blocks() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                Bbcode::new("[center]a [tooltip=hello]b[/tooltip][/center]\n[right]c[/right]"),
+                BbcodeSettings::new("", 30., Color::WHITE),
+            ))
+            .id();
+
+        app.update();
+
+        let block = app.world().get::<Children>(entity).unwrap()[0];
+        hover_span(&mut app, block, Some(2));
+        app.update();
+
+        // The popup uses the settings of the `Bbcode` entity
+        assert_eq!(popups(&mut app), [("hello".to_string(), 30.)]);
+    }
+}